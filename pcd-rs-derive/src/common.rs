@@ -2,3 +2,4 @@ pub use itertools::Itertools as _;
 
 use unzip_n::unzip_n;
 unzip_n!(pub 4);
+unzip_n!(pub 5);
@@ -0,0 +1,160 @@
+use crate::{
+    derive_de, derive_ser,
+    parse::ItemStruct,
+    utils::{has_vec_field, parse_container_attributes},
+};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::Error;
+
+pub fn f_pcd_columns_derive(item: ItemStruct) -> syn::Result<TokenStream> {
+    let struct_name = &item.ident;
+    let columns_name = format_ident!("{}Columns", struct_name);
+    let vis = &item.vis;
+    let container_opts = parse_container_attributes(&item.attrs)?;
+
+    let Some(first_field) = item.fields.first() else {
+        return Err(Error::new(
+            struct_name.span(),
+            "PcdColumns requires at least one field",
+        ));
+    };
+    let first_ident = &first_field.ident;
+    let field_idents: Vec<_> = item.fields.iter().map(|field| &field.ident).collect();
+    let field_vis = item.fields.iter().map(|field| &field.vis);
+    let field_types = item.fields.iter().map(|field| &field.ty);
+
+    let derive_de::DerivedFields {
+        derived_tokens:
+            derive_de::DerivedTokens {
+                bin_read_tokens,
+                text_read_tokens,
+                ..
+            },
+        ..
+    } = derive_de::derive_named_fields(&item.fields, &container_opts)?;
+    let derive_ser::DerivedTokens {
+        check_tokens,
+        bin_write_tokens,
+        text_write_tokens,
+        ..
+    } = derive_ser::derive_named_fields(&item.fields, &container_opts)?;
+
+    let push_tokens = quote! {
+        #(self.#field_idents.push(#field_idents);)*
+    };
+    let bind_tokens = quote! {
+        #(let #field_idents = &self.#field_idents[index];)*
+    };
+    let spec_pat = if has_vec_field(&item) {
+        quote! { spec }
+    } else {
+        quote! { _ }
+    };
+
+    let doc = format!(
+        "The columns of [{}] records, which has a `Vec` for each field.",
+        struct_name
+    );
+
+    let expanded = quote! {
+        #[doc = #doc]
+        #[derive(Clone, Default)]
+        #vis struct #columns_name {
+            #(#field_vis #field_idents: Vec<#field_types>,)*
+        }
+
+        impl #columns_name {
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            pub fn with_capacity(capacity: usize) -> Self {
+                Self {
+                    #(#field_idents: Vec::with_capacity(capacity),)*
+                }
+            }
+
+            pub fn len(&self) -> usize {
+                self.#first_ident.len()
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.#first_ident.is_empty()
+            }
+
+            pub fn push(&mut self, record: #struct_name) {
+                let #struct_name { #(#field_idents),* } = record;
+                #push_tokens
+            }
+
+            pub fn get(&self, index: usize) -> Option<#struct_name> {
+                if index >= self.len() {
+                    return None;
+                }
+                Some(#struct_name {
+                    #(#field_idents: self.#field_idents[index].clone(),)*
+                })
+            }
+        }
+
+        impl ::pcd_rs::record::PcdColumns for #columns_name {
+            type Record = #struct_name;
+
+            fn len(&self) -> usize {
+                #columns_name::len(self)
+            }
+
+            fn read_chunk<R: std::io::BufRead>(&mut self, reader: &mut R, field_defs: &::pcd_rs::metas::Schema) -> ::pcd_rs::anyhow::Result<()> {
+                use ::pcd_rs::byteorder::{LittleEndian, ReadBytesExt};
+                #[allow(unused_mut, unused_assignments, unused_variables)]
+                let () = {
+                    #bin_read_tokens
+                    #push_tokens
+                };
+                Ok(())
+            }
+
+            fn read_line<R: std::io::BufRead>(&mut self, reader: &mut R, field_defs: &::pcd_rs::metas::Schema) -> ::pcd_rs::anyhow::Result<()> {
+                ::pcd_rs::text::scan_line(reader, |mut tokens| {
+                    {
+                        let expect = field_defs.iter().fold(0, |sum, def| sum + def.count as usize);
+                        let found = tokens.clone().count();
+                        if expect != found {
+                            use ::pcd_rs::error::Error;
+                            let error = Error::new_text_token_mismatch_error(expect, found);
+                            return Err(error.into());
+                        }
+                    }
+
+                    #[allow(unused_mut, unused_assignments, unused_variables)]
+                    let () = {
+                        #text_read_tokens
+                        #push_tokens
+                    };
+                    Ok(())
+                })
+            }
+
+            fn write_chunk<W: std::io::Write>(&self, index: usize, writer: &mut W, #spec_pat: &::pcd_rs::metas::Schema) -> ::pcd_rs::anyhow::Result<()> {
+                use ::pcd_rs::byteorder::{LittleEndian, WriteBytesExt};
+                #bind_tokens
+                #check_tokens
+                #bin_write_tokens
+                Ok(())
+            }
+
+            fn write_line<W: std::io::Write>(&self, index: usize, writer: &mut W, #spec_pat: &::pcd_rs::metas::Schema, formats: &[::pcd_rs::writer::FloatFormat]) -> ::pcd_rs::anyhow::Result<()> {
+                #bind_tokens
+                #check_tokens
+                let mut tokens = Vec::<String>::new();
+                #text_write_tokens
+                let line = tokens.join(" ");
+                writeln!(writer, "{}", line)?;
+                Ok(())
+            }
+        }
+    };
+
+    Ok(expanded)
+}
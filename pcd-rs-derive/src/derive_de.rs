@@ -1,28 +1,88 @@
-use crate::{common::*, parse::ItemStruct, utils::parse_field_attributes};
+use crate::{
+    common::*,
+    derive_schema::derive_static_fields,
+    parse::{ItemStruct, NoneValue},
+    utils::{
+        check_type_options, derive_pod_assertion, option_elem_ident, parse_container_attributes,
+        parse_field_attributes, vec_elem_ident, ContainerOptions, Options,
+    },
+};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 use syn::{
-    punctuated::Punctuated, spanned::Spanned, token, Error, Field, GenericArgument, Ident,
-    PathArguments, Type, TypeArray, TypePath,
+    punctuated::Punctuated, spanned::Spanned, token, Error, Field, Ident, Path, Type, TypeArray,
+    TypePath,
 };
 
-struct DerivedTokens {
+pub struct DerivedTokens {
     pub read_spec_tokens: TokenStream,
     pub bin_read_tokens: TokenStream,
     pub text_read_tokens: TokenStream,
+    /// Reads the values from `values`, a `FieldValues` of a DynRecord
+    pub dyn_read_tokens: TokenStream,
+}
+
+pub struct DerivedFields {
+    pub derived_tokens: DerivedTokens,
+    /// The `check_schema` method if any field has a default value
+    pub check_schema_tokens: TokenStream,
 }
 
 pub fn f_pcd_record_read_derive(item: ItemStruct) -> syn::Result<TokenStream> {
     let struct_name = &item.ident;
+    let container_opts = parse_container_attributes(&item.attrs)?;
+
+    let DerivedFields {
+        derived_tokens:
+            DerivedTokens {
+                read_spec_tokens,
+                bin_read_tokens,
+                text_read_tokens,
+                dyn_read_tokens,
+            },
+        check_schema_tokens,
+    } = derive_named_fields(&item.fields, &container_opts)?;
+    let field_idents = item.fields.iter().map(|field| &field.ident);
+    let construct_tokens = quote! {
+        #struct_name {
+            #(#field_idents),*
+        }
+    };
 
-    let DerivedTokens {
-        read_spec_tokens,
-        bin_read_tokens,
-        text_read_tokens,
-    } = derive_named_fields(struct_name, &item.fields)?;
+    // Fields are fixed unless they are found in runtime or ignored
+    let has_ignore = item
+        .fields
+        .iter()
+        .map(|field| parse_field_attributes(&field.attrs))
+        .process_results(|mut opts| opts.any(|opts| opts.ignore))?;
+    let static_fields = if check_schema_tokens.is_empty() && !has_ignore {
+        derive_static_fields(&item.fields, &container_opts, false)?
+    } else {
+        None
+    };
+    let read_fields_tokens = match static_fields {
+        Some(field_defs) => quote! {
+            const READ_FIELDS: Option<&'static [::pcd_rs::record::StaticFieldDef]> = Some(&[#(#field_defs),*]);
+        },
+        None => quote! {},
+    };
+
+    let (pod_read_tokens, pod_assertion_tokens) = if container_opts.pod {
+        if !check_schema_tokens.is_empty() {
+            return Err(Error::new(
+                item.ident.span(),
+                "#[pcd(pod)] cannot be used with default fields",
+            ));
+        }
+        (derive_pod_read_chunks(), derive_pod_assertion(&item)?)
+    } else {
+        (quote! {}, quote! {})
+    };
 
     let expanded = quote! {
         impl ::pcd_rs::record::PcdDeserialize for #struct_name {
+            #read_fields_tokens
+
             fn is_dynamic() -> bool {
                 false
             }
@@ -31,119 +91,336 @@ pub fn f_pcd_record_read_derive(item: ItemStruct) -> syn::Result<TokenStream> {
                 #read_spec_tokens
             }
 
+            #check_schema_tokens
+
            fn read_chunk<R: std::io::BufRead>(reader: &mut R, field_defs: &::pcd_rs::metas::Schema) -> ::pcd_rs::anyhow::Result<#struct_name> {
                 use ::pcd_rs::byteorder::{LittleEndian, ReadBytesExt};
-                let result = { #bin_read_tokens };
+                #[allow(unused_mut, unused_assignments, unused_variables)]
+                let result = {
+                    #bin_read_tokens
+                    #construct_tokens
+                };
                 Ok(result)
             }
 
             fn read_line<R: std::io::BufRead>(reader: &mut R, field_defs: &::pcd_rs::metas::Schema) -> ::pcd_rs::anyhow::Result<#struct_name> {
-                let mut line = String::new();
-                let mut tokens = {
-                    let read_size = reader.read_line(&mut line)?;
-                    let tokens = line.split_ascii_whitespace().collect::<Vec<_>>();
-                    tokens.into_iter()
-                };
-
-                {
-                    let expect = field_defs.iter().fold(0, |sum, def| sum + def.count as usize);
-                    let (found, _) = tokens.size_hint();
-                    if expect != found {
-                        use ::pcd_rs::error::Error;
-                        let error = Error::new_text_token_mismatch_error(expect, found);
-                        return Err(error.into());
+                ::pcd_rs::text::scan_line(reader, |mut tokens| {
+                    {
+                        let expect = field_defs.iter().fold(0, |sum, def| sum + def.count as usize);
+                        let found = tokens.clone().count();
+                        if expect != found {
+                            use ::pcd_rs::error::Error;
+                            let error = Error::new_text_token_mismatch_error(expect, found);
+                            return Err(error.into());
+                        }
                     }
-                }
 
-                let result = { #text_read_tokens };
+                    #[allow(unused_mut, unused_assignments, unused_variables)]
+                    let result = {
+                        #text_read_tokens
+                        #construct_tokens
+                    };
+                    Ok(result)
+                })
+            }
+
+            #pod_read_tokens
+        }
+
+        impl std::convert::TryFrom<(&::pcd_rs::record::DynRecord, &::pcd_rs::metas::Schema)> for #struct_name {
+            type Error = ::pcd_rs::anyhow::Error;
+
+            fn try_from((record, schema): (&::pcd_rs::record::DynRecord, &::pcd_rs::metas::Schema)) -> ::pcd_rs::anyhow::Result<Self> {
+                let read_spec = <#struct_name as ::pcd_rs::record::PcdDeserialize>::read_spec();
+                let fields = ::pcd_rs::record::DynRecordFields::new(record, schema)?;
+                #[allow(unused_mut)]
+                let result = {
+                    #dyn_read_tokens
+                    #construct_tokens
+                };
                 Ok(result)
             }
         }
+
+        #pod_assertion_tokens
     };
 
     Ok(expanded)
 }
 
-fn derive_named_fields(
-    struct_name: &Ident,
+/// Derives a `read_chunks` method for `#[pcd(pod)]` structs that reads
+/// a block of records in one `read_exact` call on little-endian targets.
+fn derive_pod_read_chunks() -> TokenStream {
+    quote! {
+        fn read_chunks<R: std::io::BufRead>(reader: &mut R, field_defs: &::pcd_rs::metas::Schema, count: usize, records: &mut Vec<Self>) -> ::pcd_rs::anyhow::Result<()> {
+            if !cfg!(target_endian = "little") {
+                for _ in 0..count {
+                    records.push(Self::read_chunk(reader, field_defs)?);
+                }
+                return Ok(());
+            }
+
+            let len = records.len();
+            let size = count * std::mem::size_of::<Self>();
+            records.reserve(count);
+
+            // SAFETY: The struct is repr(C) without padding bytes and
+            // consists of primitive fields, so any zeroed or read bytes
+            // form valid records.
+            unsafe {
+                let spare = records.as_mut_ptr().add(len) as *mut u8;
+                std::ptr::write_bytes(spare, 0, size);
+                reader.read_exact(std::slice::from_raw_parts_mut(spare, size))?;
+                records.set_len(len + count);
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// Derives the statements that read the fields into local variables
+/// named after the fields.
+pub fn derive_named_fields(
     fields: &Punctuated<Field, token::Comma>,
-) -> syn::Result<DerivedTokens> {
+    container_opts: &ContainerOptions,
+) -> syn::Result<DerivedFields> {
+    let field_opts: Vec<_> = fields
+        .iter()
+        .map(|field| parse_field_attributes(&field.attrs))
+        .try_collect()?;
+
+    // Fields with default values may be missing in the data, and
+    // unknown fields may be skipped, so the field index in the schema
+    // is tracked in runtime.
+    let has_default = field_opts.iter().any(|opts| opts.default.is_some());
+    let skip_unknown = !container_opts.deny_unknown;
+    let track_index = has_default || skip_unknown;
+
     let fields: Vec<_> = fields
         .iter()
+        .zip(field_opts)
         .enumerate()
-        .map(|(field_index, field)| {
+        .map(|(field_index, (field, opts))| {
             let field_error = Error::new(
                 field.span(),
-                "expect a primitive type, array of primitive type, or Vec<_> of primitive type",
+                "expect a primitive or PcdValue type, array of such type, or Vec<_> of such type",
             );
             let field_ident = format_ident!("{}", &field.ident.as_ref().unwrap());
 
             // Check #[pcd(...)] options
-            let pcd_name_opt = {
-                let opts = parse_field_attributes(&field.attrs)?;
+            check_type_options(field, &opts)?;
+            let pcd_name_opt = container_opts.field_name(&field_ident, &opts);
 
-                match (opts.ignore, opts.rename) {
-                    (true, _) => None,
-                    (false, None) => Some(field_ident.to_string()),
-                    (false, Some(rename)) => Some(rename),
-                }
+            let index_tokens = if track_index {
+                quote! { field_index }
+            } else {
+                quote! { #field_index }
             };
-
-            let tokens = match &field.ty {
-                Type::Array(array) => derive_array_field(&field_ident, array).ok_or(field_error)?,
-                Type::Path(path) => {
-                    derive_path_field(field_index, &field_ident, path).ok_or(field_error)?
+            let name = pcd_name_opt
+                .clone()
+                .unwrap_or_else(|| field_ident.to_string());
+            let tokens = match (&opts.with, &field.ty) {
+                (Some(with), _) => derive_with_field(&field_ident, with),
+                (None, Type::Array(array)) => {
+                    derive_array_field(&field_ident, array, opts.cast.as_ref(), &name)
+                        .ok_or(field_error)?
+                }
+                (None, Type::Path(path)) => {
+                    derive_path_field(index_tokens, &field_ident, path, &opts, &name)
+                        .ok_or(field_error)?
                 }
                 _ => return Err(field_error),
             };
 
-            Ok((field_ident, pcd_name_opt, tokens))
+            Ok((field_ident, pcd_name_opt, opts.default, tokens))
         })
         .try_collect()?;
 
-    let (field_idents, read_specs, bin_read_fields, text_read_fields) = fields
+    let bin_skip_tokens = quote! {
+        ::pcd_rs::record::skip_chunk_fields(reader, &field_defs.fields[field_index..index])?;
+    };
+    let text_skip_tokens = quote! {
+        ::pcd_rs::record::skip_line_fields(&mut tokens, &field_defs.fields[field_index..index])?;
+    };
+
+    let (read_specs, defaults, bin_read_fields, text_read_fields, dyn_read_fields) = fields
         .into_iter()
-        .map(|(field_ident, pcd_name_opt, tokens)| {
-            let read_spec_tokens = tokens.read_spec_tokens;
-            let read_spec = match pcd_name_opt {
-                Some(name) => quote! { (Some(#name.to_owned()), #read_spec_tokens) },
-                None => quote! { (None, #read_spec_tokens) },
-            };
+        .enumerate()
+        .map(
+            |(spec_index, (field_ident, pcd_name_opt, default_opt, tokens))| {
+                let read_spec_tokens = tokens.read_spec_tokens;
+                let read_spec = match &pcd_name_opt {
+                    Some(name) => quote! { (Some(#name.to_owned()), #read_spec_tokens) },
+                    None => quote! { (None, #read_spec_tokens) },
+                };
 
-            (
-                field_ident,
-                read_spec,
-                tokens.bin_read_tokens,
-                tokens.text_read_tokens,
-            )
-        })
+                let wrap_read = |read_tokens: TokenStream, skip_tokens: &TokenStream| {
+                    if !track_index {
+                        return read_tokens;
+                    }
+
+                    let Some(name) = &pcd_name_opt else {
+                        return quote! {
+                            #read_tokens
+                            field_index += 1;
+                        };
+                    };
+                    let find_tokens = if skip_unknown {
+                        quote! { ::pcd_rs::record::find_field(field_defs, field_index, #name) }
+                    } else {
+                        quote! {
+                            field_defs
+                                .fields
+                                .get(field_index)
+                                .filter(|def| def.name == #name)
+                                .map(|_| field_index)
+                        }
+                    };
+                    let skip_tokens = if skip_unknown {
+                        quote! {
+                            #skip_tokens
+                            field_index = index;
+                        }
+                    } else {
+                        quote! {}
+                    };
+
+                    match &default_opt {
+                        Some(default) => quote! {
+                            let #field_ident = if let Some(index) = #find_tokens {
+                                #skip_tokens
+                                #read_tokens
+                                field_index += 1;
+                                #field_ident
+                            } else {
+                                #default
+                            };
+                        },
+                        None if skip_unknown => quote! {
+                            if let Some(index) = #find_tokens {
+                                #skip_tokens
+                            }
+                            #read_tokens
+                            field_index += 1;
+                        },
+                        None => quote! {
+                            #read_tokens
+                            field_index += 1;
+                        },
+                    }
+                };
+
+                let bin_read_field = wrap_read(tokens.bin_read_tokens, &bin_skip_tokens);
+                let text_read_field = wrap_read(tokens.text_read_tokens, &text_skip_tokens);
+
+                // DynRecord fields are looked up by name
+                let dyn_read = tokens.dyn_read_tokens;
+                let dyn_read_field = match &default_opt {
+                    Some(default) => quote! {
+                        let #field_ident = match fields.find(&read_spec[#spec_index])? {
+                            Some(mut values) => {
+                                #dyn_read
+                                #field_ident
+                            }
+                            None => #default,
+                        };
+                    },
+                    None => quote! {
+                        let #field_ident = {
+                            let mut values = fields.get(&read_spec[#spec_index])?;
+                            #dyn_read
+                            #field_ident
+                        };
+                    },
+                };
+
+                (
+                    read_spec,
+                    default_opt.is_some(),
+                    bin_read_field,
+                    text_read_field,
+                    dyn_read_field,
+                )
+            },
+        )
         .unzip_n_vec();
 
+    let (field_index_tokens, check_schema_tokens) = if track_index {
+        (
+            quote! { let mut field_index = 0usize; },
+            quote! {
+                fn check_schema(field_defs: &::pcd_rs::metas::Schema) -> ::pcd_rs::anyhow::Result<()> {
+                    ::pcd_rs::record::check_read_spec(&Self::read_spec(), &[#(#defaults),*], #skip_unknown, field_defs)
+                }
+            },
+        )
+    } else {
+        (quote! {}, quote! {})
+    };
+
+    // Skip trailing unknown fields in the record
+    let (bin_skip_rest_tokens, text_skip_rest_tokens) = if skip_unknown {
+        (
+            quote! { ::pcd_rs::record::skip_chunk_fields(reader, &field_defs.fields[field_index..])?; },
+            quote! { ::pcd_rs::record::skip_line_fields(&mut tokens, &field_defs.fields[field_index..])?; },
+        )
+    } else {
+        (quote! {}, quote! {})
+    };
+
     let read_spec_tokens = quote! { vec![#(#read_specs),*] };
     let bin_read_tokens = quote! {
+        #field_index_tokens
         #(#bin_read_fields)*
-
-        #struct_name {
-            #(#field_idents),*
-        }
+        #bin_skip_rest_tokens
     };
     let text_read_tokens = quote! {
+        #field_index_tokens
         #(#text_read_fields)*
-
-        #struct_name {
-            #(#field_idents),*
-        }
+        #text_skip_rest_tokens
     };
 
+    let dyn_read_tokens = quote! { #(#dyn_read_fields)* };
+
     let derived_tokens = DerivedTokens {
         read_spec_tokens,
         bin_read_tokens,
         text_read_tokens,
+        dyn_read_tokens,
+    };
+    Ok(DerivedFields {
+        derived_tokens,
+        check_schema_tokens,
+    })
+}
+
+fn derive_with_field(var_ident: &Ident, with: &Path) -> DerivedTokens {
+    let read_spec_tokens = quote! { ::pcd_rs::codec::decoded_kind(#with::decode), Some(1) };
+    let bin_read_tokens = quote! {
+        let #var_ident = #with::decode(::pcd_rs::codec::StoredValue::read_value(reader)?);
+    };
+    let text_read_tokens = quote! {
+        let #var_ident = #with::decode(::pcd_rs::codec::StoredValue::read_token(&mut tokens)?);
+    };
+    let dyn_read_tokens = quote! {
+        let #var_ident = #with::decode(values.next_value()?);
     };
-    Ok(derived_tokens)
+
+    DerivedTokens {
+        read_spec_tokens,
+        bin_read_tokens,
+        text_read_tokens,
+        dyn_read_tokens,
+    }
 }
 
-fn derive_array_field(var_ident: &Ident, array: &TypeArray) -> Option<DerivedTokens> {
+fn derive_array_field(
+    var_ident: &Ident,
+    array: &TypeArray,
+    cast: Option<&Ident>,
+    name: &str,
+) -> Option<DerivedTokens> {
     let len = &array.len;
     let type_ident = match &*array.elem {
         Type::Path(path) => path.path.get_ident()?,
@@ -154,7 +431,8 @@ fn derive_array_field(var_ident: &Ident, array: &TypeArray) -> Option<DerivedTok
         read_spec_tokens: read_spec,
         bin_read_tokens: bin_read,
         text_read_tokens: text_read,
-    } = make_rw_expr(type_ident)?;
+        dyn_read_tokens: dyn_read,
+    } = make_rw_expr(name, type_ident, cast)?;
 
     let read_spec_tokens = quote! { #read_spec, Some(#len) };
     let bin_read_tokens = quote! {
@@ -168,10 +446,14 @@ fn derive_array_field(var_ident: &Ident, array: &TypeArray) -> Option<DerivedTok
         let mut #var_ident = [Default::default(); #len];
 
         for idx in 0..(#len) {
-            #var_ident [idx] = {
-                let token = tokens.next().unwrap();
-                #text_read
-            };
+            #var_ident [idx] = { #text_read };
+        }
+    };
+    let dyn_read_tokens = quote! {
+        let mut #var_ident = [Default::default(); #len];
+
+        for idx in 0..(#len) {
+            #var_ident [idx] = { #dyn_read };
         }
     };
 
@@ -179,105 +461,137 @@ fn derive_array_field(var_ident: &Ident, array: &TypeArray) -> Option<DerivedTok
         read_spec_tokens,
         bin_read_tokens,
         text_read_tokens,
+        dyn_read_tokens,
     };
 
     Some(derived_tokens)
 }
 
 fn derive_path_field(
-    field_index: usize,
+    field_index: TokenStream,
     var_ident: &Ident,
     path: &TypePath,
+    opts: &Options,
+    name: &str,
 ) -> Option<DerivedTokens> {
-    match path.path.get_ident() {
-        Some(type_ident) => derive_primitive_field(var_ident, type_ident),
-        None => {
-            let segments = path.path.segments.iter().collect::<Vec<_>>();
-            let vec_args = match segments.len() {
-                1 => {
-                    // Expect Vec<_>
-                    let seg = segments[0];
-                    if seg.ident != "Vec" {
-                        return None;
-                    }
+    let cast = opts.cast.as_ref();
+
+    if let Some(type_ident) = path.path.get_ident() {
+        derive_primitive_field(var_ident, type_ident, cast, name)
+    } else if let Some(arg_ident) = option_elem_ident(path) {
+        let none = opts.none.as_ref().unwrap_or(&NoneValue::Nan);
+        derive_option_field(var_ident, arg_ident, cast, none, name)
+    } else {
+        let arg_ident = vec_elem_ident(path)?;
+        derive_vec_field(field_index, var_ident, arg_ident, cast, opts.count, name)
+    }
+}
 
-                    match &seg.arguments {
-                        PathArguments::AngleBracketed(args) => &args.args,
-                        _ => return None,
-                    }
-                }
-                3 => {
-                    // Expect std::vec::Vec<_>
-                    if segments[0].ident != "Vec"
-                        || segments[1].ident != "vec"
-                        || segments[2].ident != "Vec"
-                    {
-                        return None;
-                    }
+/// Derives an Option field, which is `None` if the value is NaN or
+/// the sentinel value.
+fn derive_option_field(
+    var_ident: &Ident,
+    arg_ident: &Ident,
+    cast: Option<&Ident>,
+    none: &NoneValue,
+    name: &str,
+) -> Option<DerivedTokens> {
+    let DerivedTokens {
+        read_spec_tokens: read_spec,
+        bin_read_tokens: bin_read,
+        text_read_tokens: text_read,
+        dyn_read_tokens: dyn_read,
+    } = make_rw_expr(name, arg_ident, cast)?;
 
-                    match &segments[2].arguments {
-                        PathArguments::AngleBracketed(args) => &args.args,
-                        _ => return None,
-                    }
-                }
-                _ => {
-                    return None;
-                }
-            };
+    let is_none = match none {
+        NoneValue::Nan => quote! { value.is_nan() },
+        NoneValue::Sentinel(sentinel) => quote! { value == #sentinel },
+    };
 
-            if vec_args.len() != 1 {
-                return None;
-            }
+    let read_spec_tokens = quote! { #read_spec, Some(1) };
+    let bin_read_tokens = quote! {
+        let #var_ident = {
+            let value: #arg_ident = { #bin_read };
+            if #is_none { None } else { Some(value) }
+        };
+    };
+    let text_read_tokens = quote! {
+        let #var_ident = {
+            let value: #arg_ident = { #text_read };
+            if #is_none { None } else { Some(value) }
+        };
+    };
+    let dyn_read_tokens = quote! {
+        let #var_ident = {
+            let value: #arg_ident = { #dyn_read };
+            if #is_none { None } else { Some(value) }
+        };
+    };
 
-            let arg_ident = match &vec_args[0] {
-                GenericArgument::Type(Type::Path(path)) => path.path.get_ident()?,
-                _ => return None,
-            };
+    let derived_tokens = DerivedTokens {
+        read_spec_tokens,
+        bin_read_tokens,
+        text_read_tokens,
+        dyn_read_tokens,
+    };
 
-            derive_vec_field(field_index, var_ident, arg_ident)
-        }
-    }
+    Some(derived_tokens)
 }
 
-fn derive_primitive_field(var_ident: &Ident, type_ident: &Ident) -> Option<DerivedTokens> {
+fn derive_primitive_field(
+    var_ident: &Ident,
+    type_ident: &Ident,
+    cast: Option<&Ident>,
+    name: &str,
+) -> Option<DerivedTokens> {
     let DerivedTokens {
         read_spec_tokens: read_spec,
         bin_read_tokens: bin_read,
         text_read_tokens: text_read,
-    } = make_rw_expr(type_ident)?;
+        dyn_read_tokens: dyn_read,
+    } = make_rw_expr(name, type_ident, cast)?;
 
     let read_spec_tokens = quote! { #read_spec, Some(1) };
     let bin_read_tokens = quote! {
         let #var_ident = { #bin_read };
     };
     let text_read_tokens = quote! {
-        let #var_ident = {
-            let token = tokens.next().unwrap();
-            #text_read
-        };
+        let #var_ident = { #text_read };
+    };
+    let dyn_read_tokens = quote! {
+        let #var_ident = { #dyn_read };
     };
 
     let derived_tokens = DerivedTokens {
         read_spec_tokens,
         bin_read_tokens,
         text_read_tokens,
+        dyn_read_tokens,
     };
 
     Some(derived_tokens)
 }
 
 fn derive_vec_field(
-    field_index: usize,
+    field_index: TokenStream,
     var_ident: &Ident,
     arg_ident: &Ident,
+    cast: Option<&Ident>,
+    count: Option<u64>,
+    name: &str,
 ) -> Option<DerivedTokens> {
     let DerivedTokens {
         read_spec_tokens: read_spec,
         bin_read_tokens: bin_read,
         text_read_tokens: text_read,
-    } = make_rw_expr(arg_ident)?;
+        dyn_read_tokens: dyn_read,
+    } = make_rw_expr(name, arg_ident, cast)?;
 
-    let read_spec_tokens = quote! { #read_spec, None };
+    let count = match count {
+        Some(count) => quote! { Some(#count as usize) },
+        None => quote! { None },
+    };
+    let read_spec_tokens = quote! { #read_spec, #count };
     let bin_read_tokens = quote! {
         let #var_ident = {
             let count = field_defs[#field_index].count as usize;
@@ -296,65 +610,139 @@ fn derive_vec_field(
             (0..count)
                 .into_iter()
                 .map(|_| {
-                    let token = tokens.next().unwrap();
                     let value = { #text_read };
                     Ok(value)
                 })
                 .collect::<::pcd_rs::anyhow::Result<Vec<_>>>()?
         };
     };
+    let dyn_read_tokens = quote! {
+        let #var_ident = (0..values.count())
+            .map(|_| {
+                let value = { #dyn_read };
+                Ok(value)
+            })
+            .collect::<::pcd_rs::anyhow::Result<Vec<_>>>()?;
+    };
 
     let derived_tokens = DerivedTokens {
         read_spec_tokens,
         bin_read_tokens,
         text_read_tokens,
+        dyn_read_tokens,
+    };
+
+    Some(derived_tokens)
+}
+
+/// Makes the expressions to read a value of the type. If `cast` is
+/// set, the value is stored as the `cast` type, and the conversion
+/// fails if the value is out of range.
+fn make_rw_expr(name: &str, type_ident: &Ident, cast: Option<&Ident>) -> Option<DerivedTokens> {
+    let Some(stored_ident) = cast else {
+        return Some(
+            make_primitive_rw_expr(type_ident).unwrap_or_else(|| make_value_rw_expr(type_ident)),
+        );
+    };
+
+    // Check if the field type is primitive
+    make_primitive_rw_expr(type_ident)?;
+
+    let DerivedTokens {
+        read_spec_tokens,
+        bin_read_tokens,
+        text_read_tokens,
+        dyn_read_tokens,
+    } = make_primitive_rw_expr(stored_ident)?;
+
+    let derived_tokens = DerivedTokens {
+        read_spec_tokens,
+        bin_read_tokens: quote! {
+            ::pcd_rs::codec::cast_value::<_, #type_ident>(#bin_read_tokens, #name)?
+        },
+        text_read_tokens: quote! {
+            ::pcd_rs::codec::cast_value::<_, #type_ident>(#text_read_tokens, #name)?
+        },
+        dyn_read_tokens: quote! {
+            ::pcd_rs::codec::cast_value::<_, #type_ident>(#dyn_read_tokens, #name)?
+        },
     };
 
     Some(derived_tokens)
 }
 
-fn make_rw_expr(type_ident: &Ident) -> Option<DerivedTokens> {
-    let (read_spec_tokens, bin_read_tokens, text_read_tokens) =
+/// Makes the expressions to read a non-primitive type implementing
+/// `PcdValue` from its stored representation.
+fn make_value_rw_expr(type_ident: &Ident) -> DerivedTokens {
+    let value_trait = quote! { ::pcd_rs::traits::PcdValue };
+    let stored_trait = quote! { ::pcd_rs::codec::StoredValue };
+
+    DerivedTokens {
+        read_spec_tokens: quote! {
+            <<#type_ident as #value_trait>::Repr as #stored_trait>::KIND
+        },
+        bin_read_tokens: quote! {
+            <#type_ident as #value_trait>::from_repr(#stored_trait::read_value(reader)?)?
+        },
+        text_read_tokens: quote! {
+            <#type_ident as #value_trait>::from_repr(#stored_trait::read_token(&mut tokens)?)?
+        },
+        dyn_read_tokens: quote! {
+            <#type_ident as #value_trait>::from_repr(values.next_value()?)?
+        },
+    }
+}
+
+fn make_primitive_rw_expr(type_ident: &Ident) -> Option<DerivedTokens> {
+    let (read_spec_tokens, bin_read_tokens, text_read_tokens, dyn_read_tokens) =
         match type_ident.to_string().as_str() {
             "u8" => (
                 quote! { ::pcd_rs::metas::ValueKind::U8 },
                 quote! { reader.read_u8()? },
-                quote! { token.parse::<u8>()? },
+                quote! { tokens.next_value::<u8>()? },
+                quote! { values.next_value::<u8>()? },
             ),
             "u16" => (
                 quote! { ::pcd_rs::metas::ValueKind::U16 },
                 quote! { reader.read_u16::<LittleEndian>()? },
-                quote! { token.parse::<u16>()? },
+                quote! { tokens.next_value::<u16>()? },
+                quote! { values.next_value::<u16>()? },
             ),
             "u32" => (
                 quote! { ::pcd_rs::metas::ValueKind::U32 },
                 quote! { reader.read_u32::<LittleEndian>()? },
-                quote! { token.parse::<u32>()? },
+                quote! { tokens.next_value::<u32>()? },
+                quote! { values.next_value::<u32>()? },
             ),
             "i8" => (
                 quote! { ::pcd_rs::metas::ValueKind::I8 },
                 quote! { reader.read_i8()? },
-                quote! { token.parse::<i8>()? },
+                quote! { tokens.next_value::<i8>()? },
+                quote! { values.next_value::<i8>()? },
             ),
             "i16" => (
                 quote! { ::pcd_rs::metas::ValueKind::I16 },
                 quote! { reader.read_i16::<LittleEndian>()? },
-                quote! { token.parse::<i16>()? },
+                quote! { tokens.next_value::<i16>()? },
+                quote! { values.next_value::<i16>()? },
             ),
             "i32" => (
                 quote! { ::pcd_rs::metas::ValueKind::I32 },
                 quote! { reader.read_i32::<LittleEndian>()? },
-                quote! { token.parse::<i32>()? },
+                quote! { tokens.next_value::<i32>()? },
+                quote! { values.next_value::<i32>()? },
             ),
             "f32" => (
                 quote! { ::pcd_rs::metas::ValueKind::F32 },
                 quote! { reader.read_f32::<LittleEndian>()? },
-                quote! { token.parse::<f32>()? },
+                quote! { tokens.next_value::<f32>()? },
+                quote! { values.next_value::<f32>()? },
             ),
             "f64" => (
                 quote! { ::pcd_rs::metas::ValueKind::F64 },
                 quote! { reader.read_f64::<LittleEndian>()? },
-                quote! { token.parse::<f64>()? },
+                quote! { tokens.next_value::<f64>()? },
+                quote! { values.next_value::<f64>()? },
             ),
             _ => return None,
         };
@@ -363,6 +751,7 @@ fn make_rw_expr(type_ident: &Ident) -> Option<DerivedTokens> {
         read_spec_tokens,
         bin_read_tokens,
         text_read_tokens,
+        dyn_read_tokens,
     };
 
     Some(derived_tokens)
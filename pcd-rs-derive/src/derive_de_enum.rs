@@ -0,0 +1,150 @@
+use crate::parse::ItemEnum;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Error, Fields};
+
+/// Derives PcdDeserialize on an enum whose variants wrap record types.
+/// The first variant whose schema matches the data is read. Readers
+/// pick the variant once, and `read_spec` is the first variant's spec.
+pub fn f_pcd_enum_read_derive(item: ItemEnum) -> syn::Result<TokenStream> {
+    let enum_name = &item.ident;
+
+    if item.variants.is_empty() {
+        return Err(Error::new(
+            enum_name.span(),
+            "PcdDeserialize requires at least one variant on the enum",
+        ));
+    }
+
+    let (variants, variant_types): (Vec<_>, Vec<_>) = item
+        .variants
+        .iter()
+        .map(|variant| match &variant.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                Ok((&variant.ident, &fields.unnamed[0].ty))
+            }
+            _ => Err(Error::new(
+                variant.ident.span(),
+                "PcdDeserialize requires a variant with exactly one unnamed field",
+            )),
+        })
+        .collect::<syn::Result<Vec<_>>>()?
+        .into_iter()
+        .unzip();
+    let first_type = variant_types[0];
+    let indices = 0..variants.len();
+
+    let trait_tokens = quote! { ::pcd_rs::record::PcdDeserialize };
+
+    let read_chunk_arms = variants
+        .iter()
+        .zip(&variant_types)
+        .zip(indices.clone())
+        .map(|((variant, ty), index)| {
+            quote! {
+                #index => <#ty as #trait_tokens>::read_chunk(reader, field_defs).map(Self::#variant),
+            }
+        });
+    let read_line_arms = variants
+        .iter()
+        .zip(&variant_types)
+        .zip(indices.clone())
+        .map(|((variant, ty), index)| {
+            quote! {
+                #index => <#ty as #trait_tokens>::read_line(reader, field_defs).map(Self::#variant),
+            }
+        });
+    let read_chunks_arms = variants
+        .iter()
+        .zip(&variant_types)
+        .zip(indices)
+        .map(|((variant, ty), index)| {
+            quote! {
+                #index => {
+                    let mut variant_records = Vec::with_capacity(count);
+                    <#ty as #trait_tokens>::read_chunks(reader, field_defs, count, &mut variant_records)?;
+                    records.extend(variant_records.into_iter().map(Self::#variant));
+                }
+            }
+        });
+
+    let expanded = quote! {
+        impl #trait_tokens for #enum_name {
+            fn is_dynamic() -> bool {
+                false
+            }
+
+            /// Gets the spec of the first variant. The variant read from
+            /// the data is picked by the schema.
+            fn read_spec() -> Vec<(Option<String>, ::pcd_rs::metas::ValueKind, Option<usize>)> {
+                <#first_type as #trait_tokens>::read_spec()
+            }
+
+            fn check_schema(field_defs: &::pcd_rs::metas::Schema) -> ::pcd_rs::anyhow::Result<()> {
+                Self::variant_index(field_defs)?;
+                Ok(())
+            }
+
+            fn read_chunk<R: std::io::BufRead>(reader: &mut R, field_defs: &::pcd_rs::metas::Schema) -> ::pcd_rs::anyhow::Result<Self> {
+                Self::read_variant_chunk(reader, field_defs, Self::variant_index(field_defs)?)
+            }
+
+            fn read_line<R: std::io::BufRead>(reader: &mut R, field_defs: &::pcd_rs::metas::Schema) -> ::pcd_rs::anyhow::Result<Self> {
+                Self::read_variant_line(reader, field_defs, Self::variant_index(field_defs)?)
+            }
+
+            fn read_chunks<R: std::io::BufRead>(reader: &mut R, field_defs: &::pcd_rs::metas::Schema, count: usize, records: &mut Vec<Self>) -> ::pcd_rs::anyhow::Result<()> {
+                Self::read_variant_chunks(reader, field_defs, Self::variant_index(field_defs)?, count, records)
+            }
+
+            fn variant_index(field_defs: &::pcd_rs::metas::Schema) -> ::pcd_rs::anyhow::Result<usize> {
+                ::pcd_rs::record::select_variant(
+                    &[#(<#variant_types as #trait_tokens>::check_schema),*],
+                    field_defs,
+                )
+            }
+
+            fn read_variant_chunk<R: std::io::BufRead>(reader: &mut R, field_defs: &::pcd_rs::metas::Schema, variant: usize) -> ::pcd_rs::anyhow::Result<Self> {
+                match variant {
+                    #(#read_chunk_arms)*
+                    _ => unreachable!(),
+                }
+            }
+
+            fn read_variant_line<R: std::io::BufRead>(reader: &mut R, field_defs: &::pcd_rs::metas::Schema, variant: usize) -> ::pcd_rs::anyhow::Result<Self> {
+                match variant {
+                    #(#read_line_arms)*
+                    _ => unreachable!(),
+                }
+            }
+
+            fn read_variant_chunks<R: std::io::BufRead>(reader: &mut R, field_defs: &::pcd_rs::metas::Schema, variant: usize, count: usize, records: &mut Vec<Self>) -> ::pcd_rs::anyhow::Result<()> {
+                records.reserve(count);
+                match variant {
+                    #(#read_chunks_arms)*
+                    _ => unreachable!(),
+                }
+                Ok(())
+            }
+        }
+
+        impl std::convert::TryFrom<(&::pcd_rs::record::DynRecord, &::pcd_rs::metas::Schema)> for #enum_name {
+            type Error = ::pcd_rs::anyhow::Error;
+
+            fn try_from(value: (&::pcd_rs::record::DynRecord, &::pcd_rs::metas::Schema)) -> ::pcd_rs::anyhow::Result<Self> {
+                let mut first_error = None;
+                #(
+                    match <#variant_types as std::convert::TryFrom<_>>::try_from(value) {
+                        Ok(record) => return Ok(Self::#variants(record)),
+                        Err(error) => {
+                            first_error.get_or_insert(error);
+                        }
+                    }
+                )*
+                Err(first_error.unwrap())
+            }
+        }
+    };
+
+    Ok(expanded)
+}
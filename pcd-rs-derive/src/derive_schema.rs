@@ -0,0 +1,113 @@
+use crate::{
+    parse::ItemStruct,
+    utils::{
+        check_type_options, is_primitive, option_elem_ident, parse_container_attributes,
+        parse_field_attributes, vec_elem_ident, ContainerOptions,
+    },
+};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{punctuated::Punctuated, spanned::Spanned, token, Error, Field, Ident, Type};
+
+pub fn f_pcd_schema_derive(item: ItemStruct) -> syn::Result<TokenStream> {
+    let struct_name = &item.ident;
+    let container_opts = parse_container_attributes(&item.attrs)?;
+    let field_defs = derive_static_fields(&item.fields, &container_opts, true)?
+        .expect("Vec fields have count options");
+
+    let expanded = quote! {
+        impl ::pcd_rs::record::PcdSchema for #struct_name {
+            const FIELDS: &'static [::pcd_rs::record::StaticFieldDef] = &[#(#field_defs),*];
+        }
+    };
+
+    Ok(expanded)
+}
+
+/// Derives the `StaticFieldDef` expressions of the fields. Ignored
+/// fields are named after the struct fields. If a Vec field has no
+/// count option, it fails when `require_count` is set, or returns
+/// `None` otherwise.
+pub fn derive_static_fields(
+    fields: &Punctuated<Field, token::Comma>,
+    container_opts: &ContainerOptions,
+    require_count: bool,
+) -> syn::Result<Option<Vec<TokenStream>>> {
+    let mut field_defs = vec![];
+
+    for field in fields.iter() {
+        let field_error = Error::new(
+            field.span(),
+            "expect a primitive or PcdValue type, array of such type, Option<_> of such type, or Vec<_> of such type with count option",
+        );
+        let field_ident = field.ident.as_ref().unwrap();
+
+        let opts = parse_field_attributes(&field.attrs)?;
+        check_type_options(field, &opts)?;
+        // Ignored fields still take a field in the data
+        let name = container_opts
+            .field_name(field_ident, &opts)
+            .unwrap_or_else(|| field_ident.to_string());
+        let cast = opts.cast.as_ref();
+
+        let (kind, count) = match (&opts.with, &field.ty) {
+            (Some(with), _) => (
+                quote! { ::pcd_rs::codec::decoded_kind(#with::decode) },
+                quote! { 1 },
+            ),
+            (None, Type::Array(array)) => {
+                let len = &array.len;
+                let elem_ident = match &*array.elem {
+                    Type::Path(path) => path.path.get_ident().ok_or(field_error)?,
+                    _ => return Err(field_error),
+                };
+                (make_kind_expr(elem_ident, cast), quote! { #len as u64 })
+            }
+            (None, Type::Path(path)) => {
+                if let Some(type_ident) = path.path.get_ident() {
+                    (make_kind_expr(type_ident, cast), quote! { 1 })
+                } else if let Some(arg_ident) = option_elem_ident(path) {
+                    (make_kind_expr(arg_ident, cast), quote! { 1 })
+                } else if let Some(arg_ident) = vec_elem_ident(path) {
+                    let count = match opts.count {
+                        Some(count) => count,
+                        None if require_count => {
+                            return Err(Error::new(
+                                field.span(),
+                                "PcdSchema requires count option on Vec fields",
+                            ))
+                        }
+                        None => return Ok(None),
+                    };
+                    (make_kind_expr(arg_ident, cast), quote! { #count })
+                } else {
+                    return Err(field_error);
+                }
+            }
+            _ => return Err(field_error),
+        };
+
+        field_defs.push(quote! {
+            ::pcd_rs::record::StaticFieldDef {
+                name: #name,
+                kind: #kind,
+                count: #count,
+            }
+        });
+    }
+
+    Ok(Some(field_defs))
+}
+
+/// Gets the stored kind of a primitive, casted or PcdValue type.
+fn make_kind_expr(type_ident: &Ident, cast: Option<&Ident>) -> TokenStream {
+    match cast {
+        Some(cast) => quote! { <#cast as ::pcd_rs::traits::Value>::KIND },
+        None if is_primitive(type_ident) => {
+            quote! { <#type_ident as ::pcd_rs::traits::Value>::KIND }
+        }
+        None => quote! {
+            <<#type_ident as ::pcd_rs::traits::PcdValue>::Repr as ::pcd_rs::codec::StoredValue>::KIND
+        },
+    }
+}
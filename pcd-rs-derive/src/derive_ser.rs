@@ -1,27 +1,95 @@
-use crate::{common::*, parse::ItemStruct, utils::parse_field_attributes};
+use crate::{
+    common::*,
+    derive_schema::derive_static_fields,
+    parse::{ItemStruct, NoneValue},
+    utils::{
+        check_type_options, derive_pod_assertion, has_vec_field, option_elem_ident,
+        parse_container_attributes, parse_field_attributes, vec_elem_ident, ContainerOptions,
+        Options,
+    },
+};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 use syn::{
-    punctuated::Punctuated, spanned::Spanned, token, Field, Ident, Type, TypeArray, TypePath,
+    punctuated::Punctuated, spanned::Spanned, token, Field, Ident, Path, Type, TypeArray, TypePath,
 };
 
-struct DerivedTokens {
+pub struct DerivedTokens {
     pub write_spec_tokens: TokenStream,
+    /// Checks before any field is written
+    pub check_tokens: TokenStream,
     pub bin_write_tokens: TokenStream,
     pub text_write_tokens: TokenStream,
+    /// Pushes the stored values to `values`, which makes a DynRecord field
+    pub dyn_write_tokens: TokenStream,
 }
 
 pub fn f_pcd_record_write_derive(item: ItemStruct) -> syn::Result<TokenStream> {
     let struct_name = &item.ident;
+    let container_opts = parse_container_attributes(&item.attrs)?;
 
     let DerivedTokens {
         write_spec_tokens,
+        check_tokens,
         bin_write_tokens,
         text_write_tokens,
-    } = derive_named_fields(struct_name, &item.fields)?;
+        dyn_write_tokens,
+    } = derive_named_fields(&item.fields, &container_opts)?;
+
+    let field_idents: Vec<_> = item.fields.iter().map(|field| &field.ident).collect();
+    let bind_tokens = quote! {
+        let #struct_name { #(#field_idents),* } = self;
+    };
+
+    // The lengths of Vec fields without the count option are taken
+    // from the record when it is converted to DynRecord
+    let mut vec_indices = vec![];
+    let mut vec_idents = vec![];
+    for (index, field) in item.fields.iter().enumerate() {
+        let is_vec = match &field.ty {
+            Type::Path(path) => vec_elem_ident(path).is_some(),
+            _ => false,
+        };
+        if is_vec && parse_field_attributes(&field.attrs)?.count.is_none() {
+            vec_indices.push(index);
+            vec_idents.push(&field.ident);
+        }
+    }
+
+    // The schema is used to check the length of Vec fields
+    let (spec_pat, dyn_spec_tokens) = if has_vec_field(&item) {
+        let dyn_spec_tokens = quote! {
+            #[allow(unused_mut)]
+            let mut spec = <#struct_name as ::pcd_rs::record::PcdSerialize>::write_spec();
+            #(
+                spec.fields[#vec_indices].count = u64::try_from(record.#vec_idents.len())
+                    .map_err(|_| ::pcd_rs::error::Error::new_invalid_argument_error("Vec field is too long"))?;
+            )*
+        };
+        (quote! { spec }, dyn_spec_tokens)
+    } else {
+        (quote! { _ }, quote! {})
+    };
+    let (write_chunks_tokens, pod_assertion_tokens) = if container_opts.pod {
+        (
+            derive_pod_write_chunks(&item)?,
+            derive_pod_assertion(&item)?,
+        )
+    } else {
+        (quote! {}, quote! {})
+    };
+
+    let write_fields_tokens = match derive_static_fields(&item.fields, &container_opts, false)? {
+        Some(field_defs) => quote! {
+            const WRITE_FIELDS: Option<&'static [::pcd_rs::record::StaticFieldDef]> = Some(&[#(#field_defs),*]);
+        },
+        None => quote! {},
+    };
 
     let expanded = quote! {
         impl ::pcd_rs::record::PcdSerialize for #struct_name {
+            #write_fields_tokens
+
             fn is_dynamic() -> bool {
                 false
             }
@@ -30,28 +98,98 @@ pub fn f_pcd_record_write_derive(item: ItemStruct) -> syn::Result<TokenStream> {
                 #write_spec_tokens
             }
 
-            fn write_chunk<R: std::io::Write>(&self, writer: &mut R, _: &::pcd_rs::metas::Schema) -> ::pcd_rs::anyhow::Result<()> {
+            fn write_chunk<R: std::io::Write>(&self, writer: &mut R, #spec_pat: &::pcd_rs::metas::Schema) -> ::pcd_rs::anyhow::Result<()> {
                 use ::pcd_rs::byteorder::{LittleEndian, WriteBytesExt};
-                { #bin_write_tokens };
+                #bind_tokens
+                #check_tokens
+                #bin_write_tokens
                 Ok(())
             }
 
-            fn write_line<R: std::io::Write>(&self, writer: &mut R, _: &::pcd_rs::metas::Schema) -> ::pcd_rs::anyhow::Result<()> {
+            fn write_line<R: std::io::Write + std::io::Seek>(&self, writer: &mut R, spec: &::pcd_rs::metas::Schema) -> ::pcd_rs::anyhow::Result<()> {
+                self.write_line_with(writer, spec, &[])
+            }
+
+            fn write_line_with<R: std::io::Write + std::io::Seek>(&self, writer: &mut R, #spec_pat: &::pcd_rs::metas::Schema, formats: &[::pcd_rs::writer::FloatFormat]) -> ::pcd_rs::anyhow::Result<()> {
+                #bind_tokens
+                #check_tokens
                 let mut tokens = Vec::<String>::new();
-                { #text_write_tokens };
+                #text_write_tokens
                 let line = tokens.join(" ");
                 writeln!(writer, "{}", line)?;
                 Ok(())
             }
+
+            #write_chunks_tokens
+        }
+
+        impl std::convert::TryFrom<&#struct_name> for ::pcd_rs::record::DynRecord {
+            type Error = ::pcd_rs::anyhow::Error;
+
+            fn try_from(record: &#struct_name) -> ::pcd_rs::anyhow::Result<Self> {
+                #dyn_spec_tokens
+                let #struct_name { #(#field_idents),* } = record;
+                #check_tokens
+                let mut fields = Vec::new();
+                #dyn_write_tokens
+                Ok(::pcd_rs::record::DynRecord(fields))
+            }
         }
+
+        impl std::convert::TryFrom<#struct_name> for ::pcd_rs::record::DynRecord {
+            type Error = ::pcd_rs::anyhow::Error;
+
+            fn try_from(record: #struct_name) -> ::pcd_rs::anyhow::Result<Self> {
+                Self::try_from(&record)
+            }
+        }
+
+        #pod_assertion_tokens
     };
 
     Ok(expanded)
 }
 
-fn derive_named_fields(
-    struct_name: &Ident,
+/// Derives a `write_chunks` method for `#[pcd(pod)]` structs that
+/// writes the records in one `write_all` call on little-endian targets.
+/// Structs with ignored fields are written record by record.
+fn derive_pod_write_chunks(item: &ItemStruct) -> syn::Result<TokenStream> {
+    for field in &item.fields {
+        if parse_field_attributes(&field.attrs)?.ignore {
+            return Ok(quote! {});
+        }
+    }
+
+    let tokens = quote! {
+        fn write_chunks<R: std::io::Write + std::io::Seek>(records: &[Self], writer: &mut R, spec: &::pcd_rs::metas::Schema) -> ::pcd_rs::anyhow::Result<()> {
+            if !cfg!(target_endian = "little") {
+                for record in records {
+                    ::pcd_rs::record::PcdSerialize::write_chunk(record, writer, spec)?;
+                }
+                return Ok(());
+            }
+
+            // SAFETY: The struct is repr(C) without padding bytes and
+            // consists of primitive fields, so every byte is initialized.
+            let bytes = unsafe {
+                std::slice::from_raw_parts(
+                    records.as_ptr() as *const u8,
+                    std::mem::size_of_val(records),
+                )
+            };
+            writer.write_all(bytes)?;
+            Ok(())
+        }
+    };
+
+    Ok(tokens)
+}
+
+/// Derives the statements that write the fields from local references
+/// named after the fields. The length checks run before any write.
+pub fn derive_named_fields(
     fields: &Punctuated<Field, token::Comma>,
+    container_opts: &ContainerOptions,
 ) -> syn::Result<DerivedTokens> {
     let fields: Vec<_> = fields
         .iter()
@@ -59,41 +197,49 @@ fn derive_named_fields(
         .map(|(field_index, field)| {
             let field_error = syn::Error::new(
                 field.span(),
-                "Type of struct field must be a primitive type or array of primitive type.",
+                "Type of struct field must be a primitive or PcdValue type, array of such type, or Vec<_> of such type.",
             );
             let field_ident = format_ident!("{}", &field.ident.as_ref().unwrap());
 
-            let pcd_name = {
-                let opts = parse_field_attributes(&field.attrs)?;
+            let opts = parse_field_attributes(&field.attrs)?;
+            check_type_options(field, &opts)?;
+            let pcd_name = container_opts.field_name(&field_ident, &opts);
+            let name = pcd_name.clone().unwrap_or_else(|| field_ident.to_string());
 
-                match (opts.ignore, opts.rename) {
-                    (true, _) => None,
-                    (false, None) => Some(field_ident.to_string()),
-                    (false, Some(rename)) => Some(rename),
+            let tokens = match (&opts.with, &field.ty) {
+                (Some(with), _) => derive_with_field(field_index, &field_ident, with),
+                (None, Type::Array(array)) => {
+                    derive_array_field(field_index, &field_ident, array, opts.cast.as_ref(), &name)
+                        .ok_or(field_error)?
                 }
-            };
-
-            let tokens = match &field.ty {
-                Type::Array(array) => derive_array_field(&field_ident, array).ok_or(field_error)?,
-                Type::Path(path) => {
-                    derive_path_field(field_index, &field_ident, path).ok_or(field_error)?
+                (None, Type::Path(path)) => {
+                    derive_path_field(field_index, &field_ident, path, &opts, &name)
+                        .ok_or(field_error)?
                 }
                 _ => return Err(field_error),
             };
 
-            Ok((field_ident, pcd_name, tokens))
+            Ok((pcd_name, tokens))
         })
         .try_collect()?;
 
-    let (field_idents, write_specs, bin_write_fields, text_write_fields) = fields
+    let (write_specs, field_checks, bin_write_fields, text_write_fields, dyn_write_fields) = fields
         .into_iter()
-        .map(|(field_ident, pcd_name, tokens)| {
+        .map(|(pcd_name, tokens)| {
             let write_spec_tokens = tokens.write_spec_tokens;
+            let dyn_write_tokens = tokens.dyn_write_tokens;
             (
-                field_ident,
                 quote! { (#pcd_name.to_owned(), #write_spec_tokens) },
+                tokens.check_tokens,
                 tokens.bin_write_tokens,
                 tokens.text_write_tokens,
+                quote! {
+                    {
+                        let mut values = Vec::new();
+                        #dyn_write_tokens
+                        fields.push(::pcd_rs::codec::StoredValue::into_field(values));
+                    }
+                },
             )
         })
         .unzip_n_vec();
@@ -103,24 +249,51 @@ fn derive_named_fields(
             .into_iter()
             .collect::<::pcd_rs::metas::Schema>()
     };
+    let check_tokens = quote! { #(#field_checks)* };
+    let bin_write_tokens = quote! { #(#bin_write_fields)* };
+    let text_write_tokens = quote! { #(#text_write_fields)* };
+    let dyn_write_tokens = quote! { #(#dyn_write_fields)* };
+
+    let derived_tokens = DerivedTokens {
+        write_spec_tokens,
+        check_tokens,
+        bin_write_tokens,
+        text_write_tokens,
+        dyn_write_tokens,
+    };
+    Ok(derived_tokens)
+}
+
+fn derive_with_field(field_index: usize, var_ident: &Ident, with: &Path) -> DerivedTokens {
+    let float_format = quote! { formats.get(#field_index).copied().unwrap_or_default() };
+
+    let write_spec_tokens = quote! { ::pcd_rs::codec::encoded_kind(#with::encode), 1 };
     let bin_write_tokens = quote! {
-        let #struct_name { #(#field_idents),* } = self;
-        #(#bin_write_fields)*
+        ::pcd_rs::codec::StoredValue::write_value(#with::encode(#var_ident), writer)?;
     };
     let text_write_tokens = quote! {
-        let #struct_name { #(#field_idents),* } = self;
-        #(#text_write_fields)*
+        tokens.push(::pcd_rs::codec::StoredValue::to_token(#with::encode(#var_ident), #float_format));
+    };
+    let dyn_write_tokens = quote! {
+        values.push(#with::encode(#var_ident));
     };
 
-    let derived_tokens = DerivedTokens {
+    DerivedTokens {
         write_spec_tokens,
+        check_tokens: quote! {},
         bin_write_tokens,
         text_write_tokens,
-    };
-    Ok(derived_tokens)
+        dyn_write_tokens,
+    }
 }
 
-fn derive_array_field(var_ident: &Ident, array: &TypeArray) -> Option<DerivedTokens> {
+fn derive_array_field(
+    field_index: usize,
+    var_ident: &Ident,
+    array: &TypeArray,
+    cast: Option<&Ident>,
+    name: &str,
+) -> Option<DerivedTokens> {
     let len = &array.len;
     let type_ident = match &*array.elem {
         Type::Path(path) => path.path.get_ident()?,
@@ -131,7 +304,9 @@ fn derive_array_field(var_ident: &Ident, array: &TypeArray) -> Option<DerivedTok
         write_spec_tokens: write_spec,
         bin_write_tokens: bin_write,
         text_write_tokens: text_write,
-    } = make_rw_expr(type_ident)?;
+        dyn_write_tokens: dyn_write,
+        ..
+    } = make_rw_expr(field_index, name, type_ident, cast)?;
 
     let write_spec_tokens = quote! { #write_spec, #len };
     let bin_write_tokens = quote! {
@@ -146,31 +321,132 @@ fn derive_array_field(var_ident: &Ident, array: &TypeArray) -> Option<DerivedTok
             #text_write;
         }
     };
+    let dyn_write_tokens = quote! {
+        for value_ref in #var_ident.iter() {
+            let value = *value_ref;
+            #dyn_write;
+        }
+    };
 
     let derived_tokens = DerivedTokens {
         write_spec_tokens,
+        check_tokens: quote! {},
         bin_write_tokens,
         text_write_tokens,
+        dyn_write_tokens,
     };
 
     Some(derived_tokens)
 }
 
 fn derive_path_field(
-    _field_index: usize,
+    field_index: usize,
     var_ident: &Ident,
     path: &TypePath,
+    opts: &Options,
+    name: &str,
 ) -> Option<DerivedTokens> {
-    let type_ident = path.path.get_ident()?;
-    derive_primitive_field(var_ident, type_ident)
+    let cast = opts.cast.as_ref();
+
+    if let Some(type_ident) = path.path.get_ident() {
+        derive_primitive_field(field_index, var_ident, type_ident, cast, name)
+    } else if let Some(arg_ident) = option_elem_ident(path) {
+        let none = opts.none.as_ref().unwrap_or(&NoneValue::Nan);
+        derive_option_field(field_index, var_ident, arg_ident, cast, none, name)
+    } else {
+        let arg_ident = vec_elem_ident(path)?;
+        derive_vec_field(field_index, var_ident, arg_ident, cast, opts.count, name)
+    }
+}
+
+/// Derives an Option field, where `None` is written as NaN or the
+/// sentinel value, and `Some` of such value fails.
+fn derive_option_field(
+    field_index: usize,
+    var_ident: &Ident,
+    arg_ident: &Ident,
+    cast: Option<&Ident>,
+    none: &NoneValue,
+    name: &str,
+) -> Option<DerivedTokens> {
+    let DerivedTokens {
+        write_spec_tokens: write_spec,
+        bin_write_tokens: bin_write,
+        text_write_tokens: text_write,
+        dyn_write_tokens: dyn_write,
+        ..
+    } = make_rw_expr(field_index, name, arg_ident, cast)?;
+
+    let none_value = match none {
+        NoneValue::Nan => quote! { #arg_ident::NAN },
+        NoneValue::Sentinel(sentinel) => quote! { #sentinel },
+    };
+
+    // Some values that read back as None are rejected before writing
+    let is_none = match none {
+        NoneValue::Nan => quote! { value.is_nan() },
+        NoneValue::Sentinel(sentinel) => quote! { *value == #sentinel },
+    };
+    let check_tokens = quote! {
+        if let Some(value) = #var_ident {
+            if #is_none {
+                let error = ::pcd_rs::error::Error::new_option_sentinel_error(#name);
+                return Err(error.into());
+            }
+        }
+    };
+    let value_tokens = quote! {
+        let value: #arg_ident = match #var_ident {
+            Some(value) => *value,
+            None => #none_value,
+        };
+    };
+
+    let write_spec_tokens = quote! { #write_spec, 1 };
+    let bin_write_tokens = quote! {
+        {
+            #value_tokens
+            #bin_write;
+        }
+    };
+    let text_write_tokens = quote! {
+        {
+            #value_tokens
+            #text_write;
+        }
+    };
+    let dyn_write_tokens = quote! {
+        {
+            #value_tokens
+            #dyn_write;
+        }
+    };
+
+    let derived_tokens = DerivedTokens {
+        write_spec_tokens,
+        check_tokens,
+        bin_write_tokens,
+        text_write_tokens,
+        dyn_write_tokens,
+    };
+
+    Some(derived_tokens)
 }
 
-fn derive_primitive_field(var_ident: &Ident, type_ident: &Ident) -> Option<DerivedTokens> {
+fn derive_primitive_field(
+    field_index: usize,
+    var_ident: &Ident,
+    type_ident: &Ident,
+    cast: Option<&Ident>,
+    name: &str,
+) -> Option<DerivedTokens> {
     let DerivedTokens {
         write_spec_tokens: write_spec,
         bin_write_tokens: bin_write,
         text_write_tokens: text_write,
-    } = make_rw_expr(type_ident)?;
+        dyn_write_tokens: dyn_write,
+        ..
+    } = make_rw_expr(field_index, name, type_ident, cast)?;
 
     let write_spec_tokens = quote! { #write_spec, 1 };
     let bin_write_tokens = quote! {
@@ -185,17 +461,162 @@ fn derive_primitive_field(var_ident: &Ident, type_ident: &Ident) -> Option<Deriv
             #text_write;
         }
     };
+    let dyn_write_tokens = quote! {
+        {
+            let value = *#var_ident;
+            #dyn_write;
+        }
+    };
 
     let derived_tokens = DerivedTokens {
         write_spec_tokens,
+        check_tokens: quote! {},
         bin_write_tokens,
         text_write_tokens,
+        dyn_write_tokens,
     };
 
     Some(derived_tokens)
 }
 
-fn make_rw_expr(type_ident: &Ident) -> Option<DerivedTokens> {
+/// Derives a Vec field. The count is zero unless it is set by the
+/// count option, and the length is checked against the schema.
+fn derive_vec_field(
+    field_index: usize,
+    var_ident: &Ident,
+    arg_ident: &Ident,
+    cast: Option<&Ident>,
+    count: Option<u64>,
+    name: &str,
+) -> Option<DerivedTokens> {
+    let DerivedTokens {
+        write_spec_tokens: write_spec,
+        bin_write_tokens: bin_write,
+        text_write_tokens: text_write,
+        dyn_write_tokens: dyn_write,
+        ..
+    } = make_rw_expr(field_index, name, arg_ident, cast)?;
+
+    let count = count.unwrap_or(0);
+    let write_spec_tokens = quote! { #write_spec, #count };
+    let check_tokens = quote! {
+        let expect = spec[#field_index].count as usize;
+        if #var_ident.len() != expect {
+            use ::pcd_rs::error::Error;
+            let error = Error::new_field_size_mismatch_error(#name, expect, #var_ident.len());
+            return Err(error.into());
+        }
+    };
+    let bin_write_tokens = quote! {
+        for value_ref in #var_ident.iter() {
+            let value = *value_ref;
+            #bin_write;
+        }
+    };
+    let text_write_tokens = quote! {
+        for value_ref in #var_ident.iter() {
+            let value = *value_ref;
+            #text_write;
+        }
+    };
+    let dyn_write_tokens = quote! {
+        for value_ref in #var_ident.iter() {
+            let value = *value_ref;
+            #dyn_write;
+        }
+    };
+
+    let derived_tokens = DerivedTokens {
+        write_spec_tokens,
+        check_tokens,
+        bin_write_tokens,
+        text_write_tokens,
+        dyn_write_tokens,
+    };
+
+    Some(derived_tokens)
+}
+
+/// Makes the expressions to write a value of the type. If `cast` is
+/// set, the value is converted to the `cast` type, which fails if the
+/// value is out of range.
+fn make_rw_expr(
+    field_index: usize,
+    name: &str,
+    type_ident: &Ident,
+    cast: Option<&Ident>,
+) -> Option<DerivedTokens> {
+    let Some(stored_ident) = cast else {
+        return Some(
+            make_primitive_rw_expr(field_index, type_ident)
+                .unwrap_or_else(|| make_value_rw_expr(field_index, type_ident)),
+        );
+    };
+
+    // Check if the field type is primitive
+    make_primitive_rw_expr(field_index, type_ident)?;
+
+    let DerivedTokens {
+        write_spec_tokens,
+        bin_write_tokens,
+        text_write_tokens,
+        dyn_write_tokens,
+        ..
+    } = make_primitive_rw_expr(field_index, stored_ident)?;
+
+    let derived_tokens = DerivedTokens {
+        write_spec_tokens,
+        check_tokens: quote! {},
+        bin_write_tokens: quote! {
+            {
+                let value = ::pcd_rs::codec::cast_value::<_, #stored_ident>(value, #name)?;
+                #bin_write_tokens
+            }
+        },
+        text_write_tokens: quote! {
+            {
+                let value = ::pcd_rs::codec::cast_value::<_, #stored_ident>(value, #name)?;
+                #text_write_tokens
+            }
+        },
+        dyn_write_tokens: quote! {
+            {
+                let value = ::pcd_rs::codec::cast_value::<_, #stored_ident>(value, #name)?;
+                #dyn_write_tokens
+            }
+        },
+    };
+
+    Some(derived_tokens)
+}
+
+/// Makes the expressions to write a non-primitive type implementing
+/// `PcdValue` as its stored representation.
+fn make_value_rw_expr(field_index: usize, type_ident: &Ident) -> DerivedTokens {
+    let value_trait = quote! { ::pcd_rs::traits::PcdValue };
+    let stored_trait = quote! { ::pcd_rs::codec::StoredValue };
+    let float_format = quote! { formats.get(#field_index).copied().unwrap_or_default() };
+
+    DerivedTokens {
+        write_spec_tokens: quote! {
+            <<#type_ident as #value_trait>::Repr as #stored_trait>::KIND
+        },
+        check_tokens: quote! {},
+        bin_write_tokens: quote! {
+            #stored_trait::write_value(#value_trait::to_repr(&value), writer)?
+        },
+        text_write_tokens: quote! {
+            tokens.push(#stored_trait::to_token(#value_trait::to_repr(&value), #float_format))
+        },
+        dyn_write_tokens: quote! {
+            values.push(#value_trait::to_repr(&value))
+        },
+    }
+}
+
+fn make_primitive_rw_expr(field_index: usize, type_ident: &Ident) -> Option<DerivedTokens> {
+    let float_format = quote! { formats.get(#field_index).copied().unwrap_or_default() };
+
     let (write_spec_tokens, bin_write_tokens, text_write_tokens) =
         match type_ident.to_string().as_str() {
             "u8" => (
@@ -231,20 +652,22 @@ fn make_rw_expr(type_ident: &Ident) -> Option<DerivedTokens> {
             "f32" => (
                 quote! { ::pcd_rs::metas::ValueKind::F32 },
                 quote! { writer.write_f32::<LittleEndian>(value)? },
-                quote! { tokens.push(f32::to_string(&value)) },
+                quote! { tokens.push(#float_format.format(value)) },
             ),
             "f64" => (
                 quote! { ::pcd_rs::metas::ValueKind::F64 },
                 quote! { writer.write_f64::<LittleEndian>(value)? },
-                quote! { tokens.push(f64::to_string(&value)) },
+                quote! { tokens.push(#float_format.format(value)) },
             ),
             _ => return None,
         };
 
     let derived_tokens = DerivedTokens {
         write_spec_tokens,
+        check_tokens: quote! {},
         bin_write_tokens,
         text_write_tokens,
+        dyn_write_tokens: quote! { values.push(value) },
     };
 
     Some(derived_tokens)
@@ -0,0 +1,57 @@
+use crate::utils::enum_repr;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Fields};
+
+pub fn f_pcd_value_derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    let enum_name = &input.ident;
+
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new(
+            enum_name.span(),
+            "PcdValue can only be derived on enums",
+        ));
+    };
+    let repr = enum_repr(&input.attrs).ok_or_else(|| {
+        Error::new(
+            enum_name.span(),
+            "PcdValue requires #[repr(u8)], #[repr(u16)] or #[repr(u32)] on the enum",
+        )
+    })?;
+
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new(
+                variant.ident.span(),
+                "PcdValue requires a fieldless enum",
+            ));
+        }
+    }
+    let variants: Vec<_> = data.variants.iter().map(|variant| &variant.ident).collect();
+
+    let expanded = quote! {
+        impl ::pcd_rs::traits::PcdValue for #enum_name {
+            type Repr = #repr;
+
+            fn to_repr(&self) -> #repr {
+                match self {
+                    #(Self::#variants => Self::#variants as #repr,)*
+                }
+            }
+
+            fn from_repr(repr: #repr) -> ::pcd_rs::anyhow::Result<Self> {
+                #(
+                    if repr == Self::#variants as #repr {
+                        return Ok(Self::#variants);
+                    }
+                )*
+
+                use ::pcd_rs::error::Error;
+                let error = Error::new_unknown_discriminant_error(stringify!(#enum_name), repr as u64);
+                Err(error.into())
+            }
+        }
+    };
+
+    Ok(expanded)
+}
@@ -1,26 +1,45 @@
 mod common;
+mod derive_columns;
 mod derive_de;
+mod derive_de_enum;
+mod derive_schema;
 mod derive_ser;
+mod derive_value;
 mod parse;
 mod utils;
 
-use parse::ItemStruct;
+use parse::{Item, ItemStruct};
 use proc_macro::TokenStream;
 
 /// Derives PcdDeserialize trait on normal struct or tuple struct.
 ///
 /// The field type can be either primitive, array of primitive or [Vec](std::vec::Vec) of primitive.
+/// Types implementing `PcdValue`, like `bool`, are accepted in place of primitives.
+///
+/// On enums, each variant wraps a record type, like `Xyz(PointXyz)`. The
+/// first variant whose schema matches the data is read.
+///
+/// It also implements `TryFrom<(&DynRecord, &Schema)>`, which maps the
+/// `DynRecord` fields to the struct fields by name.
 #[proc_macro_derive(PcdDeserialize, attributes(pcd))]
 pub fn pcd_record_read_derive(input: TokenStream) -> TokenStream {
-    let input = syn::parse_macro_input!(input as ItemStruct);
-    let derive_read_tokens =
-        derive_de::f_pcd_record_read_derive(input).unwrap_or_else(|err| err.to_compile_error());
+    let input = syn::parse_macro_input!(input as Item);
+    let derive_read_tokens = match input {
+        Item::Struct(item) => derive_de::f_pcd_record_read_derive(item),
+        Item::Enum(item) => derive_de_enum::f_pcd_enum_read_derive(item),
+    }
+    .unwrap_or_else(|err| err.to_compile_error());
     TokenStream::from(derive_read_tokens)
 }
 
 /// Derives PcdSerialize trait on normal struct or tuple struct.
 ///
-/// The field type can be either primitive or array of primitive.
+/// The field type can be either primitive, array of primitive or [Vec](std::vec::Vec) of primitive.
+/// Types implementing `PcdValue`, like `bool`, are accepted in place of primitives.
+///
+/// It also implements `TryFrom<T>` and `TryFrom<&T>` for `DynRecord`. They
+/// are fallible conversions instead of `Into<DynRecord>`, because casts,
+/// `Option` sentinels and `Vec` counts are checked as in writing.
 #[proc_macro_derive(PcdSerialize, attributes(pcd))]
 pub fn pcd_record_write_derive(input: TokenStream) -> TokenStream {
     let input = syn::parse_macro_input!(input as ItemStruct);
@@ -28,3 +47,39 @@ pub fn pcd_record_write_derive(input: TokenStream) -> TokenStream {
         derive_ser::f_pcd_record_write_derive(input).unwrap_or_else(|err| err.to_compile_error());
     TokenStream::from(derive_write_tokens)
 }
+
+/// Derives a `{Name}Columns` type that stores the records in one
+/// [Vec](std::vec::Vec) per field, and implements PcdColumns trait on it.
+///
+/// The field attributes are the same as [PcdDeserialize](macro@PcdDeserialize)
+/// and [PcdSerialize](macro@PcdSerialize).
+#[proc_macro_derive(PcdColumns, attributes(pcd))]
+pub fn pcd_columns_derive(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as ItemStruct);
+    let derive_columns_tokens =
+        derive_columns::f_pcd_columns_derive(input).unwrap_or_else(|err| err.to_compile_error());
+    TokenStream::from(derive_columns_tokens)
+}
+
+/// Derives PcdSchema trait on normal struct, which describes the
+/// record layout as constants.
+///
+/// The field types are the same as [PcdSerialize](macro@PcdSerialize),
+/// except that [Vec](std::vec::Vec) fields require the count option.
+#[proc_macro_derive(PcdSchema, attributes(pcd))]
+pub fn pcd_schema_derive(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as ItemStruct);
+    let derive_schema_tokens =
+        derive_schema::f_pcd_schema_derive(input).unwrap_or_else(|err| err.to_compile_error());
+    TokenStream::from(derive_schema_tokens)
+}
+
+/// Derives PcdValue trait on fieldless enum with `#[repr(u8)]`,
+/// `#[repr(u16)]` or `#[repr(u32)]`. The enum must be [Copy].
+#[proc_macro_derive(PcdValue)]
+pub fn pcd_value_derive(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    let derive_value_tokens =
+        derive_value::f_pcd_value_derive(input).unwrap_or_else(|err| err.to_compile_error());
+    TokenStream::from(derive_value_tokens)
+}
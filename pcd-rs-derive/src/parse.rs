@@ -1,35 +1,81 @@
 use once_cell::sync::Lazy;
+use proc_macro2::TokenStream;
+use quote::quote;
 use regex::Regex;
 use syn::{
     braced,
+    ext::IdentExt,
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
-    token, Attribute, Error, Field, Ident, LitStr, Result, Token, Visibility,
+    Attribute, Error, ExprPath, Field, Ident, Lit, LitBool, LitInt, LitStr, Path, Result, Token,
+    Variant, Visibility,
 };
 
 pub struct ItemStruct {
     pub attrs: Vec<Attribute>,
     pub vis: Visibility,
-    pub struct_token: Token![struct],
     pub ident: Ident,
-    pub brace_token: token::Brace,
     pub fields: Punctuated<Field, Token![,]>,
 }
 
 impl Parse for ItemStruct {
     fn parse(input: ParseStream) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        input.parse::<Token![struct]>()?;
+        let ident = input.parse()?;
         let content;
+        braced!(content in input);
+        let fields = content.parse_terminated(Field::parse_named, Token![,])?;
+
         Ok(ItemStruct {
-            attrs: input.call(Attribute::parse_outer)?,
-            vis: input.parse()?,
-            struct_token: input.parse()?,
-            ident: input.parse()?,
-            brace_token: braced!(content in input),
-            fields: content.parse_terminated(Field::parse_named, Token![,])?,
+            attrs,
+            vis,
+            ident,
+            fields,
         })
     }
 }
 
+pub struct ItemEnum {
+    pub ident: Ident,
+    pub variants: Punctuated<Variant, Token![,]>,
+}
+
+impl Parse for ItemEnum {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.call(Attribute::parse_outer)?;
+        input.parse::<Visibility>()?;
+        input.parse::<Token![enum]>()?;
+        let ident = input.parse()?;
+        let content;
+        braced!(content in input);
+        let variants = content.parse_terminated(Variant::parse, Token![,])?;
+
+        Ok(ItemEnum { ident, variants })
+    }
+}
+
+/// A struct or an enum item.
+pub enum Item {
+    Struct(ItemStruct),
+    Enum(ItemEnum),
+}
+
+impl Parse for Item {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let fork = input.fork();
+        fork.call(Attribute::parse_outer)?;
+        let _: Visibility = fork.parse()?;
+
+        if fork.peek(Token![enum]) {
+            Ok(Item::Enum(input.parse()?))
+        } else {
+            Ok(Item::Struct(input.parse()?))
+        }
+    }
+}
+
 pub struct AttrList {
     pub options: Punctuated<AttrOption, Token![,]>,
 }
@@ -45,6 +91,15 @@ impl Parse for AttrList {
 pub enum AttrOption {
     Rename(RenameAttr),
     Ignore(IgnoreAttr),
+    Pod(PodAttr),
+    Default(DefaultAttr),
+    With(WithAttr),
+    As(AsAttr),
+    Count(CountAttr),
+    None(NoneAttr),
+    RenameAll(RenameAllAttr),
+    Prefix(PrefixAttr),
+    DenyUnknown(DenyUnknownAttr),
 }
 
 impl AttrOption {
@@ -63,6 +118,102 @@ impl AttrOption {
             None
         }
     }
+
+    pub fn as_pod(&self) -> Option<&PodAttr> {
+        if let Self::Pod(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_default(&self) -> Option<&DefaultAttr> {
+        if let Self::Default(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_with(&self) -> Option<&WithAttr> {
+        if let Self::With(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_as(&self) -> Option<&AsAttr> {
+        if let Self::As(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_count(&self) -> Option<&CountAttr> {
+        if let Self::Count(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_none(&self) -> Option<&NoneAttr> {
+        if let Self::None(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_rename_all(&self) -> Option<&RenameAllAttr> {
+        if let Self::RenameAll(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_prefix(&self) -> Option<&PrefixAttr> {
+        if let Self::Prefix(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_deny_unknown(&self) -> Option<&DenyUnknownAttr> {
+        if let Self::DenyUnknown(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
+    /// Checks if the option is only allowed on the struct.
+    pub fn is_container_option(&self) -> bool {
+        matches!(
+            self,
+            Self::Pod(_) | Self::RenameAll(_) | Self::Prefix(_) | Self::DenyUnknown(_)
+        )
+    }
+
+    pub fn ident(&self) -> &Ident {
+        match self {
+            Self::Rename(v) => &v.ident,
+            Self::Ignore(v) => &v.ident,
+            Self::Pod(v) => &v.ident,
+            Self::Default(v) => &v.ident,
+            Self::With(v) => &v.ident,
+            Self::As(v) => &v.ident,
+            Self::Count(v) => &v.ident,
+            Self::None(v) => &v.ident,
+            Self::RenameAll(v) => &v.ident,
+            Self::Prefix(v) => &v.ident,
+            Self::DenyUnknown(v) => &v.ident,
+        }
+    }
 }
 
 impl From<RenameAttr> for AttrOption {
@@ -77,10 +228,62 @@ impl From<IgnoreAttr> for AttrOption {
     }
 }
 
+impl From<PodAttr> for AttrOption {
+    fn from(v: PodAttr) -> Self {
+        Self::Pod(v)
+    }
+}
+
+impl From<DefaultAttr> for AttrOption {
+    fn from(v: DefaultAttr) -> Self {
+        Self::Default(v)
+    }
+}
+
+impl From<WithAttr> for AttrOption {
+    fn from(v: WithAttr) -> Self {
+        Self::With(v)
+    }
+}
+
+impl From<AsAttr> for AttrOption {
+    fn from(v: AsAttr) -> Self {
+        Self::As(v)
+    }
+}
+
+impl From<CountAttr> for AttrOption {
+    fn from(v: CountAttr) -> Self {
+        Self::Count(v)
+    }
+}
+
+impl From<NoneAttr> for AttrOption {
+    fn from(v: NoneAttr) -> Self {
+        Self::None(v)
+    }
+}
+
+impl From<RenameAllAttr> for AttrOption {
+    fn from(v: RenameAllAttr) -> Self {
+        Self::RenameAll(v)
+    }
+}
+
+impl From<PrefixAttr> for AttrOption {
+    fn from(v: PrefixAttr) -> Self {
+        Self::Prefix(v)
+    }
+}
+
+impl From<DenyUnknownAttr> for AttrOption {
+    fn from(v: DenyUnknownAttr) -> Self {
+        Self::DenyUnknown(v)
+    }
+}
+
 pub struct RenameAttr {
     pub ident: Ident,
-    pub eq_token: Token![=],
-    pub lit: LitStr,
     pub rename: String,
 }
 
@@ -88,16 +291,128 @@ pub struct IgnoreAttr {
     pub ident: Ident,
 }
 
+pub struct PodAttr {
+    pub ident: Ident,
+}
+
+pub struct DefaultAttr {
+    pub ident: Ident,
+    pub path: Option<ExprPath>,
+}
+
+pub struct WithAttr {
+    pub ident: Ident,
+    pub path: Path,
+}
+
+pub struct AsAttr {
+    pub ident: Ident,
+    pub ty: Ident,
+}
+
+pub struct CountAttr {
+    pub ident: Ident,
+    pub count: u64,
+}
+
+pub struct NoneAttr {
+    pub ident: Ident,
+    pub value: NoneValue,
+}
+
+pub struct RenameAllAttr {
+    pub ident: Ident,
+    pub rule: RenameRule,
+}
+
+pub struct PrefixAttr {
+    pub ident: Ident,
+    pub prefix: String,
+}
+
+pub struct DenyUnknownAttr {
+    pub ident: Ident,
+    pub deny: bool,
+}
+
+/// The case convention of field names in `rename_all` option.
+#[derive(Clone, Copy)]
+pub enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+}
+
+impl RenameRule {
+    const RULES: &'static [(&'static str, Self)] = &[
+        ("lowercase", Self::Lower),
+        ("UPPERCASE", Self::Upper),
+        ("PascalCase", Self::Pascal),
+        ("camelCase", Self::Camel),
+        ("snake_case", Self::Snake),
+        ("SCREAMING_SNAKE_CASE", Self::ScreamingSnake),
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::RULES
+            .iter()
+            .find(|(rule_name, _)| *rule_name == name)
+            .map(|(_, rule)| *rule)
+    }
+
+    /// Converts a snake_case field name by the rule.
+    pub fn apply(self, name: &str) -> String {
+        match self {
+            Self::Lower => name.to_lowercase(),
+            Self::Upper => name.to_uppercase(),
+            Self::Pascal | Self::Camel => {
+                let pascal: String = name
+                    .split('_')
+                    .map(|word| {
+                        let mut chars = word.chars();
+                        match chars.next() {
+                            Some(first) => first.to_uppercase().chain(chars).collect(),
+                            None => String::new(),
+                        }
+                    })
+                    .collect();
+                match self {
+                    Self::Camel => {
+                        let mut chars = pascal.chars();
+                        match chars.next() {
+                            Some(first) => first.to_lowercase().chain(chars).collect(),
+                            None => pascal,
+                        }
+                    }
+                    _ => pascal,
+                }
+            }
+            Self::Snake => name.to_owned(),
+            Self::ScreamingSnake => name.to_uppercase(),
+        }
+    }
+}
+
+/// The encoding of `None` in an Option field.
+#[derive(Clone)]
+pub enum NoneValue {
+    Nan,
+    Sentinel(TokenStream),
+}
+
+static NAME_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[[:word:]]+$").unwrap());
+
 impl Parse for AttrOption {
     fn parse(input: ParseStream) -> Result<Self> {
-        let ident: Ident = input.parse()?;
+        // `as` is a keyword
+        let ident = input.call(Ident::parse_any)?;
 
         let attr: Self = match ident.to_string().as_str() {
             "rename" => {
-                static NAME_REGEX: Lazy<Regex> =
-                    Lazy::new(|| Regex::new(r"^[[:word:]]+$").unwrap());
-
-                let eq_token = input.parse()?;
+                let _: Token![=] = input.parse()?;
                 let lit: LitStr = input.parse()?;
                 let rename = lit.value();
 
@@ -105,15 +420,92 @@ impl Parse for AttrOption {
                     .find(&rename)
                     .ok_or_else(|| Error::new(lit.span(), "invalid name"))?;
 
-                RenameAttr {
-                    ident,
-                    eq_token,
-                    lit,
-                    rename,
-                }
-                .into()
+                RenameAttr { ident, rename }.into()
             }
             "ignore" => IgnoreAttr { ident }.into(),
+            "pod" => PodAttr { ident }.into(),
+            "default" => {
+                let path = if input.peek(Token![=]) {
+                    let _: Token![=] = input.parse()?;
+                    let lit: LitStr = input.parse()?;
+                    Some(lit.parse()?)
+                } else {
+                    None
+                };
+                DefaultAttr { ident, path }.into()
+            }
+            "with" => {
+                let _: Token![=] = input.parse()?;
+                let lit: LitStr = input.parse()?;
+                let path = lit.parse()?;
+                WithAttr { ident, path }.into()
+            }
+            "as" => {
+                const TYPES: &[&str] = &["u8", "u16", "u32", "i8", "i16", "i32", "f32", "f64"];
+
+                let _: Token![=] = input.parse()?;
+                let lit: LitStr = input.parse()?;
+                let ty: Ident = lit.parse()?;
+                if !TYPES.contains(&ty.to_string().as_str()) {
+                    return Err(Error::new(lit.span(), "expect a primitive type"));
+                }
+                AsAttr { ident, ty }.into()
+            }
+            "count" => {
+                let _: Token![=] = input.parse()?;
+                let lit: LitInt = input.parse()?;
+                let count: u64 = lit.base10_parse()?;
+                if count == 0 {
+                    return Err(Error::new(lit.span(), "count must not be zero"));
+                }
+                CountAttr { ident, count }.into()
+            }
+            "none" => {
+                let _: Token![=] = input.parse()?;
+                let value = if input.peek(LitStr) {
+                    let lit: LitStr = input.parse()?;
+                    if lit.value() != "nan" {
+                        return Err(Error::new(lit.span(), "expect \"nan\" or a number"));
+                    }
+                    NoneValue::Nan
+                } else {
+                    let minus: Option<Token![-]> = input.parse()?;
+                    let lit: Lit = input.parse()?;
+                    if !matches!(lit, Lit::Int(_) | Lit::Float(_)) {
+                        return Err(Error::new(lit.span(), "expect \"nan\" or a number"));
+                    }
+                    NoneValue::Sentinel(quote! { #minus #lit })
+                };
+                NoneAttr { ident, value }.into()
+            }
+            "rename_all" => {
+                let _: Token![=] = input.parse()?;
+                let lit: LitStr = input.parse()?;
+                let rule = RenameRule::from_name(&lit.value()).ok_or_else(|| {
+                    let names: Vec<_> = RenameRule::RULES.iter().map(|(name, _)| *name).collect();
+                    Error::new(lit.span(), format!("expect one of {}", names.join(", ")))
+                })?;
+                RenameAllAttr { ident, rule }.into()
+            }
+            "prefix" => {
+                let _: Token![=] = input.parse()?;
+                let lit: LitStr = input.parse()?;
+                let prefix = lit.value();
+                NAME_REGEX
+                    .find(&prefix)
+                    .ok_or_else(|| Error::new(lit.span(), "invalid prefix"))?;
+                PrefixAttr { ident, prefix }.into()
+            }
+            "deny_unknown" => {
+                let deny = if input.peek(Token![=]) {
+                    let _: Token![=] = input.parse()?;
+                    let lit: LitBool = input.parse()?;
+                    lit.value
+                } else {
+                    true
+                };
+                DenyUnknownAttr { ident, deny }.into()
+            }
             name => {
                 return Err(Error::new(
                     ident.span(),
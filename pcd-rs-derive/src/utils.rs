@@ -1,30 +1,46 @@
-use crate::common::*;
-use syn::{spanned::Spanned, AttrStyle, Attribute, Error};
+use crate::{common::*, parse::ItemStruct};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parenthesized, spanned::Spanned, token, AttrStyle, Attribute, Error, Field, GenericArgument,
+    Ident, Path, PathArguments, Type, TypePath,
+};
 
-use crate::parse::{AttrList, AttrOption};
+use crate::parse::{AttrList, AttrOption, NoneValue, RenameRule};
+
+fn parse_attr_options(attrs: &[Attribute]) -> syn::Result<Vec<AttrOption>> {
+    let options: Vec<_> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("pcd"))
+        .map(|attr| {
+            if attr.style == AttrStyle::Outer {
+                Ok(attr)
+            } else {
+                Err(Error::new(
+                    attr.span(),
+                    "inner pcd attribute is not supported",
+                ))
+            }
+        })
+        .map(|attr| -> syn::Result<_> {
+            let attr = attr?;
+            let attr_list: AttrList = attr.parse_args()?;
+            Ok(attr_list)
+        })
+        .try_collect()?;
+    Ok(options.into_iter().flat_map(|list| list.options).collect())
+}
 
 pub fn parse_field_attributes(attrs: &[Attribute]) -> syn::Result<Options> {
     {
-        let options: Vec<_> = attrs
-            .iter()
-            .filter(|attr| attr.path().is_ident("pcd"))
-            .map(|attr| {
-                if attr.style == AttrStyle::Outer {
-                    Ok(attr)
-                } else {
-                    Err(Error::new(
-                        attr.span(),
-                        "inner pcd attribute is not supported",
-                    ))
-                }
-            })
-            .map(|attr| -> syn::Result<_> {
-                let attr = attr?;
-                let attr_list: AttrList = attr.parse_args()?;
-                Ok(attr_list)
-            })
-            .try_collect()?;
-        let options: Vec<AttrOption> = options.into_iter().flat_map(|list| list.options).collect();
+        let options = parse_attr_options(attrs)?;
+
+        if let Some(opt) = options.iter().find(|opt| opt.is_container_option()) {
+            return Err(syn::Error::new(
+                opt.ident().span(),
+                format!("{} option is only allowed on the struct", opt.ident()),
+            ));
+        }
 
         let ignore_option = {
             let mut ignore_opts = options.iter().filter_map(|opt| opt.as_ignore()).fuse();
@@ -49,14 +65,376 @@ pub fn parse_field_attributes(attrs: &[Attribute]) -> syn::Result<Options> {
             rename_opt
         };
 
+        let default_option = {
+            let mut default_opts = options.iter().filter_map(|opt| opt.as_default()).fuse();
+            let default_opt = default_opts.next();
+            if let Some(opt) = default_opts.next() {
+                return Err(syn::Error::new(
+                    opt.ident.span(),
+                    "default option cannot specified more than once",
+                ));
+            }
+            default_opt
+        };
+        if let (Some(opt), Some(_)) = (default_option, ignore_option) {
+            return Err(syn::Error::new(
+                opt.ident.span(),
+                "default option cannot be used with ignore option",
+            ));
+        }
+
+        let with_option = {
+            let mut with_opts = options.iter().filter_map(|opt| opt.as_with()).fuse();
+            let with_opt = with_opts.next();
+            if let Some(opt) = with_opts.next() {
+                return Err(syn::Error::new(
+                    opt.ident.span(),
+                    "with option cannot specified more than once",
+                ));
+            }
+            with_opt
+        };
+        let as_option = {
+            let mut as_opts = options.iter().filter_map(|opt| opt.as_as()).fuse();
+            let as_opt = as_opts.next();
+            if let Some(opt) = as_opts.next() {
+                return Err(syn::Error::new(
+                    opt.ident.span(),
+                    "as option cannot specified more than once",
+                ));
+            }
+            as_opt
+        };
+        if let (Some(opt), Some(_)) = (as_option, with_option) {
+            return Err(syn::Error::new(
+                opt.ident.span(),
+                "as option cannot be used with with option",
+            ));
+        }
+
+        let count_option = {
+            let mut count_opts = options.iter().filter_map(|opt| opt.as_count()).fuse();
+            let count_opt = count_opts.next();
+            if let Some(opt) = count_opts.next() {
+                return Err(syn::Error::new(
+                    opt.ident.span(),
+                    "count option cannot specified more than once",
+                ));
+            }
+            count_opt
+        };
+
+        let none_option = {
+            let mut none_opts = options.iter().filter_map(|opt| opt.as_none()).fuse();
+            let none_opt = none_opts.next();
+            if let Some(opt) = none_opts.next() {
+                return Err(syn::Error::new(
+                    opt.ident.span(),
+                    "none option cannot specified more than once",
+                ));
+            }
+            none_opt
+        };
+
         Ok(Options {
             ignore: ignore_option.is_some(),
             rename: rename_option.map(|opt| opt.rename.clone()),
+            default: default_option.map(|opt| match &opt.path {
+                Some(path) => quote! { #path() },
+                None => quote! { Default::default() },
+            }),
+            with: with_option.map(|opt| opt.path.clone()),
+            cast: as_option.map(|opt| opt.ty.clone()),
+            count: count_option.map(|opt| opt.count),
+            none: none_option.map(|opt| opt.value.clone()),
         })
     }
 }
 
+pub fn parse_container_attributes(attrs: &[Attribute]) -> syn::Result<ContainerOptions> {
+    let options = parse_attr_options(attrs)?;
+
+    if let Some(opt) = options.iter().find(|opt| !opt.is_container_option()) {
+        return Err(syn::Error::new(
+            opt.ident().span(),
+            format!("{} option is only allowed on fields", opt.ident()),
+        ));
+    }
+
+    let rename_all_option = {
+        let mut rename_all_opts = options.iter().filter_map(|opt| opt.as_rename_all()).fuse();
+        let rename_all_opt = rename_all_opts.next();
+        if let Some(opt) = rename_all_opts.next() {
+            return Err(syn::Error::new(
+                opt.ident.span(),
+                "rename_all option cannot specified more than once",
+            ));
+        }
+        rename_all_opt
+    };
+    let prefix_option = {
+        let mut prefix_opts = options.iter().filter_map(|opt| opt.as_prefix()).fuse();
+        let prefix_opt = prefix_opts.next();
+        if let Some(opt) = prefix_opts.next() {
+            return Err(syn::Error::new(
+                opt.ident.span(),
+                "prefix option cannot specified more than once",
+            ));
+        }
+        prefix_opt
+    };
+    let deny_unknown_option = {
+        let mut deny_unknown_opts = options
+            .iter()
+            .filter_map(|opt| opt.as_deny_unknown())
+            .fuse();
+        let deny_unknown_opt = deny_unknown_opts.next();
+        if let Some(opt) = deny_unknown_opts.next() {
+            return Err(syn::Error::new(
+                opt.ident.span(),
+                "deny_unknown option cannot specified more than once",
+            ));
+        }
+        deny_unknown_opt
+    };
+
+    let pod = options.iter().any(|opt| opt.as_pod().is_some());
+    if let (true, Some(opt)) = (pod, deny_unknown_option) {
+        if !opt.deny {
+            return Err(syn::Error::new(
+                opt.ident.span(),
+                "#[pcd(pod)] cannot be used with deny_unknown = false",
+            ));
+        }
+    }
+
+    Ok(ContainerOptions {
+        pod,
+        rename_all: rename_all_option.map(|opt| opt.rule),
+        prefix: prefix_option.map(|opt| opt.prefix.clone()),
+        deny_unknown: deny_unknown_option.iter().all(|opt| opt.deny),
+    })
+}
+
+/// Checks if the struct has `#[repr(C)]` attribute.
+pub fn has_repr_c(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("repr"))
+        .any(|attr| {
+            let mut is_c = false;
+            let _ = attr.parse_nested_meta(|meta| {
+                is_c |= meta.path.is_ident("C");
+                // Skip arguments like align(8)
+                if meta.input.peek(token::Paren) {
+                    let _args;
+                    parenthesized!(_args in meta.input);
+                }
+                Ok(())
+            });
+            is_c
+        })
+}
+
+/// Gets the integer type in `#[repr(u8)]`, `#[repr(u16)]` or
+/// `#[repr(u32)]` attribute.
+pub fn enum_repr(attrs: &[Attribute]) -> Option<Ident> {
+    let mut repr = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            if let Some(ident) = meta.path.get_ident() {
+                if ["u8", "u16", "u32"].contains(&ident.to_string().as_str()) {
+                    repr = Some(ident.clone());
+                }
+            }
+            Ok(())
+        });
+    }
+    repr
+}
+
+/// Checks that a `#[pcd(pod)]` struct is `#[repr(C)]` and consists of
+/// primitive or array of primitive fields. It returns a constant
+/// assertion that the struct has no padding bytes.
+pub fn derive_pod_assertion(item: &ItemStruct) -> syn::Result<TokenStream> {
+    if !has_repr_c(&item.attrs) {
+        return Err(Error::new(
+            item.ident.span(),
+            "#[pcd(pod)] requires #[repr(C)] on the struct",
+        ));
+    }
+
+    for field in &item.fields {
+        if parse_field_attributes(&field.attrs)?.is_converted() {
+            return Err(Error::new(
+                field.span(),
+                "#[pcd(pod)] cannot be used with with or as options",
+            ));
+        }
+        if !is_primitive_or_array(&field.ty) {
+            return Err(Error::new(
+                field.ty.span(),
+                "#[pcd(pod)] requires a primitive type or array of primitive type",
+            ));
+        }
+    }
+
+    let struct_name = &item.ident;
+    let field_types = item.fields.iter().map(|field| &field.ty);
+
+    Ok(quote! {
+        const _: () = assert!(
+            std::mem::size_of::<#struct_name>() == 0 #(+ std::mem::size_of::<#field_types>())*,
+            "#[pcd(pod)] requires a struct without padding bytes"
+        );
+    })
+}
+
+/// Checks that the count option is only set on Vec fields, and the
+/// none option is only set on Option fields. Option fields of integer
+/// types must have the none option.
+pub fn check_type_options(field: &Field, opts: &Options) -> syn::Result<()> {
+    let path = match &field.ty {
+        Type::Path(path) if opts.with.is_none() => Some(path),
+        _ => None,
+    };
+    let is_vec = path.is_some_and(|path| vec_elem_ident(path).is_some());
+    let option_elem = path.and_then(option_elem_ident);
+
+    if opts.count.is_some() && !is_vec {
+        return Err(Error::new(
+            field.span(),
+            "count option is only allowed on Vec fields",
+        ));
+    }
+
+    let Some(elem) = option_elem else {
+        if opts.none.is_some() {
+            return Err(Error::new(
+                field.span(),
+                "none option is only allowed on Option fields",
+            ));
+        }
+        return Ok(());
+    };
+
+    let is_float = elem == "f32" || elem == "f64";
+    match (&opts.none, is_float) {
+        (None, false) => Err(Error::new(
+            field.span(),
+            "Option field of non-float type requires the none option",
+        )),
+        (Some(NoneValue::Nan), false) => Err(Error::new(
+            field.span(),
+            r#"none = "nan" requires Option of float type"#,
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Checks if the struct has a Vec field, whose length is checked
+/// against the schema when written.
+pub fn has_vec_field(item: &ItemStruct) -> bool {
+    item.fields.iter().any(|field| match &field.ty {
+        Type::Path(path) => vec_elem_ident(path).is_some(),
+        _ => false,
+    })
+}
+
+/// Gets the element type of `Vec<T>` or `std::vec::Vec<T>`.
+pub fn vec_elem_ident(path: &TypePath) -> Option<&Ident> {
+    std_generic_arg(path, "vec", "Vec")
+}
+
+/// Gets the element type of `Option<T>` or `std::option::Option<T>`.
+pub fn option_elem_ident(path: &TypePath) -> Option<&Ident> {
+    std_generic_arg(path, "option", "Option")
+}
+
+fn std_generic_arg<'a>(path: &'a TypePath, module: &str, name: &str) -> Option<&'a Ident> {
+    let segments: Vec<_> = path.path.segments.iter().collect();
+    let segment = match segments.as_slice() {
+        [segment] => segment,
+        [std, std_mod, segment] if std.ident == "std" && std_mod.ident == module => segment,
+        _ => return None,
+    };
+    if segment.ident != name {
+        return None;
+    }
+
+    let args = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => &args.args,
+        _ => return None,
+    };
+    match args.iter().collect::<Vec<_>>().as_slice() {
+        [GenericArgument::Type(Type::Path(path))] => path.path.get_ident(),
+        _ => None,
+    }
+}
+
+pub fn is_primitive_or_array(ty: &Type) -> bool {
+    match ty {
+        Type::Array(array) => is_primitive_or_array(&array.elem),
+        Type::Path(path) => path.path.get_ident().is_some_and(is_primitive),
+        _ => false,
+    }
+}
+
+pub fn is_primitive(ident: &Ident) -> bool {
+    matches!(
+        ident.to_string().as_str(),
+        "u8" | "u16" | "u32" | "i8" | "i16" | "i32" | "f32" | "f64"
+    )
+}
+
 pub struct Options {
     pub ignore: bool,
     pub rename: Option<String>,
+    /// The expression of the default value
+    pub default: Option<TokenStream>,
+    /// The codec module of the field
+    pub with: Option<Path>,
+    /// The stored primitive type of the field
+    pub cast: Option<Ident>,
+    /// The fixed length of the Vec field
+    pub count: Option<u64>,
+    /// The encoding of `None` in the Option field
+    pub none: Option<NoneValue>,
+}
+
+impl Options {
+    /// Checks if the field is stored as another type.
+    pub fn is_converted(&self) -> bool {
+        self.with.is_some() || self.cast.is_some()
+    }
+}
+
+pub struct ContainerOptions {
+    pub pod: bool,
+    /// The case convention of field names
+    pub rename_all: Option<RenameRule>,
+    /// The prefix prepended to field names
+    pub prefix: Option<String>,
+    /// Whether unknown fields in the data are rejected
+    pub deny_unknown: bool,
+}
+
+impl ContainerOptions {
+    /// Gets the field name in PCD data, or `None` if the field is
+    /// ignored. An explicit `rename` is used as is.
+    pub fn field_name(&self, field_ident: &Ident, opts: &Options) -> Option<String> {
+        if opts.ignore {
+            return None;
+        }
+        if let Some(rename) = &opts.rename {
+            return Some(rename.clone());
+        }
+
+        let name = field_ident.to_string();
+        let name = match self.rename_all {
+            Some(rule) => rule.apply(&name),
+            None => name,
+        };
+        Some(format!("{}{}", self.prefix.as_deref().unwrap_or(""), name))
+    }
 }
@@ -1,3 +1,5 @@
+#![allow(clippy::approx_constant)]
+
 use anyhow::Result;
 use pcd_rs::{DataKind, DynRecord, DynWriter, Field, Schema, ValueKind, WriterInit};
 use std::iter::FromIterator;
@@ -7,7 +9,7 @@ fn main() -> Result<()> {
     let path = "test_files/dump_ascii_untyped.pcd";
 
     // point data
-    let dump_points = vec![
+    let dump_points = [
         DynRecord(vec![
             Field::F32(vec![3.14159]),
             Field::U8(vec![2, 1, 7]),
@@ -33,16 +35,17 @@ fn main() -> Result<()> {
     ];
 
     let mut writer: DynWriter<_> = WriterInit {
-        width: 300,
+        width: dump_points.len() as u64,
         height: 1,
         viewpoint: Default::default(),
         data_kind: DataKind::Ascii,
         schema: Some(Schema::from_iter(schema)),
+        ..Default::default()
     }
     .create(path)?;
 
     for point in dump_points.iter() {
-        writer.push(&point)?;
+        writer.push(point)?;
     }
 
     writer.finish()?;
@@ -1,3 +1,5 @@
+#![allow(clippy::approx_constant)]
+
 use anyhow::Result;
 use pcd_rs::{DataKind, PcdDeserialize, PcdSerialize, WriterInit};
 
@@ -14,7 +16,7 @@ pub fn main() -> Result<()> {
     let path = "test_files/dump_ascii_static.pcd";
 
     // point data
-    let dump_points = vec![
+    let dump_points = [
         Point {
             x: 3.14159,
             y: [2, 1, 7],
@@ -34,16 +36,17 @@ pub fn main() -> Result<()> {
 
     // serialize points
     let mut writer = WriterInit {
-        width: 300,
+        width: dump_points.len() as u64,
         height: 1,
         viewpoint: Default::default(),
         data_kind: DataKind::Ascii,
         schema: None,
+        ..Default::default()
     }
     .create::<Point, _>(path)?;
 
     for point in dump_points.iter() {
-        writer.push(&point)?;
+        writer.push(point)?;
     }
 
     writer.finish()?;
@@ -0,0 +1,285 @@
+//! Codecs that convert struct fields to stored PCD values.
+//!
+//! A field with `#[pcd(with = "module")]` attribute is stored as a
+//! primitive value and converted by the functions in the module.
+//!
+//! ```ignore
+//! pub fn encode(value: &FieldType) -> StoredType;
+//! pub fn decode(value: StoredType) -> FieldType;
+//! ```
+//!
+//! The `StoredType` implements [StoredValue], which determines the
+//! type of the field in PCD data. It is usually a primitive type. The
+//! [rgb] and [rgba] codecs pack colors like PCL does.
+#![cfg_attr(
+    feature = "derive",
+    doc = r##"
+```rust
+use pcd_rs::{PcdDeserialize, PcdSerialize};
+
+#[derive(PcdDeserialize, PcdSerialize)]
+pub struct Point {
+    x: f32,
+    y: f32,
+    z: f32,
+    #[pcd(with = "pcd_rs::codec::rgb")]
+    rgb: [u8; 3],
+    #[pcd(as = "f32")]
+    intensity: f64,
+}
+```
+"##
+)]
+
+use crate::{error::Error, metas::ValueKind, record::Field, text::Tokens, writer::FloatFormat};
+use anyhow::Result;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use num_traits::{Float, NumCast};
+use std::{
+    fmt::{Display, LowerExp},
+    io::prelude::*,
+};
+
+/// Packs `[r, g, b]` colors into `F32` values, which is the `rgb`
+/// field in PCL.
+pub mod rgb {
+    use super::PackedRgb;
+
+    pub fn encode(value: &[u8; 3]) -> PackedRgb {
+        let [r, g, b] = *value;
+        PackedRgb(u32::from_be_bytes([0, r, g, b]))
+    }
+
+    pub fn decode(value: PackedRgb) -> [u8; 3] {
+        let [_, r, g, b] = value.0.to_be_bytes();
+        [r, g, b]
+    }
+}
+
+/// Packs `[r, g, b, a]` colors into `U32` values, which is the `rgba`
+/// field in PCL.
+pub mod rgba {
+    pub fn encode(value: &[u8; 4]) -> u32 {
+        let [r, g, b, a] = *value;
+        u32::from_be_bytes([a, r, g, b])
+    }
+
+    pub fn decode(value: u32) -> [u8; 4] {
+        let [a, r, g, b] = value.to_be_bytes();
+        [r, g, b, a]
+    }
+}
+
+/// A `0xAARRGGBB` color stored in a `F32` field.
+///
+/// The bits are stored in binary data. Like PCL, Ascii data has the
+/// integer value of the color, because opaque colors are NaN as floats.
+/// Non-integer tokens are read as the bits of the float.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct PackedRgb(pub u32);
+
+impl StoredValue for PackedRgb {
+    const KIND: ValueKind = ValueKind::F32;
+
+    fn read_value<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self(reader.read_u32::<LittleEndian>()?))
+    }
+
+    fn read_token(tokens: &mut Tokens<'_>) -> Result<Self> {
+        let value: f64 = tokens.next_value()?;
+        let is_integer = value.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(&value);
+
+        if is_integer {
+            Ok(Self(value as u32))
+        } else {
+            Ok(Self((value as f32).to_bits()))
+        }
+    }
+
+    fn write_value<W: Write>(self, writer: &mut W) -> Result<()> {
+        Ok(writer.write_u32::<LittleEndian>(self.0)?)
+    }
+
+    fn to_token(self, _format: FloatFormat) -> String {
+        self.0.to_string()
+    }
+
+    fn from_field(field: &Field, index: usize) -> Option<Self> {
+        match field {
+            Field::F32(values) => values.get(index).map(|value| Self(value.to_bits())),
+            _ => None,
+        }
+    }
+
+    fn into_field(values: Vec<Self>) -> Field {
+        Field::F32(
+            values
+                .into_iter()
+                .map(|value| f32::from_bits(value.0))
+                .collect(),
+        )
+    }
+}
+
+/// Gets the stored kind of an encoding function.
+#[doc(hidden)]
+pub const fn encoded_kind<T, S>(_encode: fn(&T) -> S) -> ValueKind
+where
+    T: ?Sized,
+    S: StoredValue,
+{
+    S::KIND
+}
+
+/// Gets the stored kind of a decoding function.
+#[doc(hidden)]
+pub const fn decoded_kind<S, T>(_decode: fn(S) -> T) -> ValueKind
+where
+    S: StoredValue,
+{
+    S::KIND
+}
+
+/// Converts a value of a `#[pcd(as = "...")]` field, failing with
+/// [FieldCastError](crate::Error::FieldCastError) if the value is out
+/// of range of the target type.
+#[doc(hidden)]
+pub fn cast_value<S, T>(value: S, name: &str) -> Result<T>
+where
+    S: NumCast,
+    T: NumCast + StoredValue,
+{
+    T::from(value).ok_or_else(|| Error::new_field_cast_error(name, T::KIND).into())
+}
+
+/// The values returned by encoding functions, which are primitive
+/// types or types like [PackedRgb].
+pub trait StoredValue: Copy {
+    const KIND: ValueKind;
+
+    fn read_value<R: Read>(reader: &mut R) -> Result<Self>;
+    fn read_token(tokens: &mut Tokens<'_>) -> Result<Self>;
+    fn write_value<W: Write>(self, writer: &mut W) -> Result<()>;
+    fn to_token(self, format: FloatFormat) -> String;
+
+    /// Gets the value at the index of a [Field] of the same kind.
+    fn from_field(field: &Field, index: usize) -> Option<Self>;
+
+    /// Makes a [Field] of the values.
+    fn into_field(values: Vec<Self>) -> Field;
+}
+
+impl StoredValue for u8 {
+    const KIND: ValueKind = ValueKind::U8;
+
+    fn read_value<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(reader.read_u8()?)
+    }
+
+    fn read_token(tokens: &mut Tokens<'_>) -> Result<Self> {
+        tokens.next_value()
+    }
+
+    fn write_value<W: Write>(self, writer: &mut W) -> Result<()> {
+        Ok(writer.write_u8(self)?)
+    }
+
+    fn to_token(self, _format: FloatFormat) -> String {
+        self.to_string()
+    }
+
+    fn from_field(field: &Field, index: usize) -> Option<Self> {
+        match field {
+            Field::U8(values) => values.get(index).copied(),
+            _ => None,
+        }
+    }
+
+    fn into_field(values: Vec<Self>) -> Field {
+        Field::U8(values)
+    }
+}
+
+impl StoredValue for i8 {
+    const KIND: ValueKind = ValueKind::I8;
+
+    fn read_value<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(reader.read_i8()?)
+    }
+
+    fn read_token(tokens: &mut Tokens<'_>) -> Result<Self> {
+        tokens.next_value()
+    }
+
+    fn write_value<W: Write>(self, writer: &mut W) -> Result<()> {
+        Ok(writer.write_i8(self)?)
+    }
+
+    fn to_token(self, _format: FloatFormat) -> String {
+        self.to_string()
+    }
+
+    fn from_field(field: &Field, index: usize) -> Option<Self> {
+        match field {
+            Field::I8(values) => values.get(index).copied(),
+            _ => None,
+        }
+    }
+
+    fn into_field(values: Vec<Self>) -> Field {
+        Field::I8(values)
+    }
+}
+
+macro_rules! impl_stored_value {
+    ($ty:ty, $kind:ident, $read:ident, $write:ident, $to_token:ident) => {
+        impl StoredValue for $ty {
+            const KIND: ValueKind = ValueKind::$kind;
+
+            fn read_value<R: Read>(reader: &mut R) -> Result<Self> {
+                Ok(reader.$read::<LittleEndian>()?)
+            }
+
+            fn read_token(tokens: &mut Tokens<'_>) -> Result<Self> {
+                tokens.next_value()
+            }
+
+            fn write_value<W: Write>(self, writer: &mut W) -> Result<()> {
+                Ok(writer.$write::<LittleEndian>(self)?)
+            }
+
+            fn to_token(self, format: FloatFormat) -> String {
+                $to_token(self, format)
+            }
+
+            fn from_field(field: &Field, index: usize) -> Option<Self> {
+                match field {
+                    Field::$kind(values) => values.get(index).copied(),
+                    _ => None,
+                }
+            }
+
+            fn into_field(values: Vec<Self>) -> Field {
+                Field::$kind(values)
+            }
+        }
+    };
+}
+
+impl_stored_value!(u16, U16, read_u16, write_u16, int_token);
+impl_stored_value!(u32, U32, read_u32, write_u32, int_token);
+impl_stored_value!(i16, I16, read_i16, write_i16, int_token);
+impl_stored_value!(i32, I32, read_i32, write_i32, int_token);
+impl_stored_value!(f32, F32, read_f32, write_f32, float_token);
+impl_stored_value!(f64, F64, read_f64, write_f64, float_token);
+
+fn int_token<T: ToString>(value: T, _format: FloatFormat) -> String {
+    value.to_string()
+}
+
+fn float_token<T>(value: T, format: FloatFormat) -> String
+where
+    T: Float + Display + LowerExp,
+{
+    format.format(value)
+}
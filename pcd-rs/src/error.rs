@@ -24,6 +24,36 @@ pub enum Error {
     TextTokenMismatchError { expect: usize, found: usize },
     #[error("Invalid argument: {desc}")]
     InvalidArgumentError { desc: String },
+    #[error("field name must not be empty")]
+    EmptyFieldNameError,
+    #[error(r#"field "{name}" is specified more than once"#)]
+    DuplicateFieldError { name: String },
+    #[error(r#"count of field "{name}" must not be zero"#)]
+    ZeroCountError { name: String },
+    #[error(
+        r#"field "{name}" at offset {offset} does not follow the previous field ending at offset {end}"#
+    )]
+    FieldOffsetError { name: String, offset: u64, end: u64 },
+    #[error("fields take {expect} bytes, but the record size is {found} bytes")]
+    RecordSizeError { expect: u64, found: u64 },
+    #[error(r#"field "{name}" is not found in schema"#)]
+    FieldNotFoundError { name: String },
+    #[error(r#"field "{name}" has kind {found:?}, but expect {expect:?}"#)]
+    FieldKindMismatchError {
+        name: String,
+        expect: ValueKind,
+        found: ValueKind,
+    },
+    #[error(r#"values of field "{name}" cannot be converted to {kind:?}"#)]
+    FieldCastError { name: String, kind: ValueKind },
+    #[error("expect {expect} bytes of point data, but found {found} bytes")]
+    TruncatedDataError { expect: u64, found: u64 },
+    #[error(r#"token "{token}" cannot be parsed as {kind:?}"#)]
+    InvalidTokenError { token: String, kind: ValueKind },
+    #[error("value {value} is not a valid {type_name}")]
+    UnknownDiscriminantError { type_name: String, value: u64 },
+    #[error(r#"Some value of field "{name}" is the value that stores None"#)]
+    OptionSentinelError { name: String },
 }
 
 impl Error {
@@ -69,4 +99,73 @@ impl Error {
             desc: desc.to_owned(),
         }
     }
+
+    pub fn new_duplicate_field_error(name: &str) -> Error {
+        Error::DuplicateFieldError {
+            name: name.to_owned(),
+        }
+    }
+
+    pub fn new_zero_count_error(name: &str) -> Error {
+        Error::ZeroCountError {
+            name: name.to_owned(),
+        }
+    }
+
+    pub fn new_field_offset_error(name: &str, offset: u64, end: u64) -> Error {
+        Error::FieldOffsetError {
+            name: name.to_owned(),
+            offset,
+            end,
+        }
+    }
+
+    pub fn new_record_size_error(expect: u64, found: u64) -> Error {
+        Error::RecordSizeError { expect, found }
+    }
+
+    pub fn new_field_not_found_error(name: &str) -> Error {
+        Error::FieldNotFoundError {
+            name: name.to_owned(),
+        }
+    }
+
+    pub fn new_field_kind_mismatch_error(name: &str, expect: ValueKind, found: ValueKind) -> Error {
+        Error::FieldKindMismatchError {
+            name: name.to_owned(),
+            expect,
+            found,
+        }
+    }
+
+    pub fn new_field_cast_error(name: &str, kind: ValueKind) -> Error {
+        Error::FieldCastError {
+            name: name.to_owned(),
+            kind,
+        }
+    }
+
+    pub fn new_truncated_data_error(expect: u64, found: u64) -> Error {
+        Error::TruncatedDataError { expect, found }
+    }
+
+    pub fn new_invalid_token_error(token: &[u8], kind: ValueKind) -> Error {
+        Error::InvalidTokenError {
+            token: String::from_utf8_lossy(token).into_owned(),
+            kind,
+        }
+    }
+
+    pub fn new_unknown_discriminant_error(type_name: &str, value: u64) -> Error {
+        Error::UnknownDiscriminantError {
+            type_name: type_name.to_owned(),
+            value,
+        }
+    }
+
+    pub fn new_option_sentinel_error(name: &str) -> Error {
+        Error::OptionSentinelError {
+            name: name.to_owned(),
+        }
+    }
 }
@@ -80,11 +80,12 @@
 //!
 //! // Build a writer
 //! let mut writer: DynWriter<_> = WriterInit {
-//!     width: 300,
+//!     width: points.len() as u64,
 //!     height: 1,
 //!     viewpoint: Default::default(),
 //!     data_kind: DataKind::Ascii,
 //!     schema: Some(Schema::from_iter(schema)),
+//!     ..Default::default()
 //! }
 //! .create("test_files/dump_ascii_untyped.pcd")?;
 //!
@@ -175,11 +176,12 @@ let points = [
 
 // serialize points
 let mut writer = WriterInit {
-    width: 300,
+    width: points.len() as u64,
     height: 1,
     viewpoint: Default::default(),
     data_kind: DataKind::Ascii,
     schema: None,
+    ..Default::default()
 }
 .create("test_files/dump_ascii_static.pcd")?;
 
@@ -199,28 +201,93 @@ attributes.
 
 - `#[pcd(rename = "NEW_NAME")]` sets the field name on the written PCD data.
 - `#[pcd(ignore)]` instructs the de/serializer to ignore the field.
+- `#[pcd(as = "f32")]` stores a primitive field, or the elements of an
+  array field, as another primitive type. Values out of range of either
+  type fail with [FieldCastError](Error::FieldCastError).
+- `#[pcd(with = "module")]` stores the field by the `encode` and
+  `decode` functions in the module. See [codec] for details.
+- `#[pcd(count = N)]` sets the length of a `Vec` field. Without it,
+  the writer takes the length from the schema given to [WriterInit].
+- `#[pcd(none = "nan")]` or `#[pcd(none = 0xFFFF)]` sets how `None` is
+  stored in an `Option` field. Float fields use NaN by default. Writing
+  `Some` of that value fails with
+  [OptionSentinelError](Error::OptionSentinelError).
+
+[PcdDeserialize] also supports `#[pcd(default)]` and
+`#[pcd(default = "path::to::fn")]`. If the field is missing in the PCD
+data, it is filled with `Default::default()` or the function result
+instead of failing with a schema mismatch. Serializers ignore the attribute.
+
+The `#[pcd(pod)]` struct attribute, together with `#[repr(C)]`, lets
+binary records be read and written in blocks of raw bytes. See
+[record] for details.
+
+Field names can also be set by struct attributes. An explicit `rename`
+takes precedence over them.
+
+- `#[pcd(rename_all = "camelCase")]` converts the field names by the case
+  convention, which is one of `lowercase`, `UPPERCASE`, `PascalCase`,
+  `camelCase`, `snake_case` or `SCREAMING_SNAKE_CASE`.
+- `#[pcd(prefix = "sensor_")]` prepends the prefix to the field names.
+- `#[pcd(deny_unknown = false)]` lets [PcdDeserialize] skip fields that
+  are not in the struct. The struct fields must still be in the same
+  order as in the data. Unknown fields are rejected by default.
+
+[PcdDeserialize] can be derived on an enum whose variants wrap record
+types, such as `Xyz(PointXyz)`. The reader picks the first variant
+whose schema matches the data once, and yields it for every point. The
+conversion from [DynRecord] takes the first variant that converts.
+`read_spec()` of the enum returns the spec of the first variant.
+
+The derives also convert between records and [DynRecord] by field
+names. [PcdDeserialize] implements `TryFrom<(&DynRecord, &Schema)>`,
+which fails with a typed [Error] on missing fields or kind and count
+mismatches. [PcdSerialize] implements `TryFrom<T>` and `TryFrom<&T>`
+for [DynRecord], which fail if a Vec field does not have its fixed count.
+It is `TryFrom` rather than `Into<DynRecord>`, because `as` casts and
+`Option` sentinels can fail as well.
+
+`#[derive(PcdSchema)]` implements [PcdSchema], which gives the fields
+and the binary record size as constants. It accepts the same attributes.
+
+`#[derive(PcdColumns)]` generates a `{Name}Columns` type with a `Vec` for
+each field, which [Reader::read_columns()] and [Writer::push_columns()]
+fill and write without building records.
 "##
 )]
 
+// Lets derived code refer to `::pcd_rs` within this crate
+extern crate self as pcd_rs;
+
 #[doc(hidden)]
 pub use anyhow;
 #[doc(hidden)]
 pub use byteorder;
 
+pub mod codec;
 pub mod error;
 pub mod metas;
+#[cfg(feature = "derive")]
+pub mod pcl;
 pub mod prelude;
 pub mod reader;
 pub mod record;
+pub mod ros;
+pub mod text;
 pub mod traits;
+pub mod transform;
 mod utils;
 pub mod writer;
 
 pub use error::Error;
-pub use metas::{DataKind, FieldDef, PcdMeta, Schema, TypeKind, ValueKind, ViewPoint};
+pub use metas::{
+    DataKind, FieldDef, PcdMeta, Schema, SchemaBuilder, TypeKind, ValueKind, ViewPoint,
+};
 #[cfg(feature = "derive")]
-pub use pcd_rs_derive::{PcdDeserialize, PcdSerialize};
+pub use pcd_rs_derive::{PcdColumns, PcdDeserialize, PcdSchema, PcdSerialize, PcdValue};
 pub use reader::{DynReader, Reader};
-pub use record::{DynRecord, Field, PcdDeserialize, PcdSerialize};
-pub use traits::Value;
-pub use writer::{DynWriter, Writer, WriterInit};
+pub use record::{
+    DynRecord, Field, PcdColumns, PcdDeserialize, PcdSchema, PcdSerialize, StaticFieldDef,
+};
+pub use traits::{PcdValue, Value};
+pub use writer::{AsciiFormat, DynWriter, FloatFormat, Writer, WriterInit};
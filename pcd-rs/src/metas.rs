@@ -1,11 +1,29 @@
 //! Types for PCD metadata.
 
-use std::{iter::FromIterator, ops::Index};
+use crate::error::Error;
+use anyhow::Result;
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{prelude::*, BufReader},
+    iter::FromIterator,
+    ops::Index,
+    path::Path,
+};
 
 /// The struct keep meta data of PCD file.
+///
+/// The `version` keeps the VERSION entry as written, which is either
+/// `0.7` or `.7`. The `comments` keeps the raw text after `#` in header
+/// lines in order, without the line break. They are written after `#`
+/// as leading comment lines when the header is written. Inline comments
+/// after an entry, like `FIELDS x y z # position`, are kept as well, but
+/// they are moved to the leading comment lines, so their positions are
+/// not preserved when the header is rewritten.
 #[derive(Debug, Clone, PartialEq)]
 pub struct PcdMeta {
     pub version: String,
+    pub comments: Vec<String>,
     pub width: u64,
     pub height: u64,
     pub viewpoint: ViewPoint,
@@ -14,6 +32,66 @@ pub struct PcdMeta {
     pub field_defs: Schema,
 }
 
+impl PcdMeta {
+    /// Parses the PCD header from a reader without reading the point data.
+    ///
+    /// It returns the meta data and the byte offset where the point
+    /// data starts. The reader is left at the start of point data.
+    pub fn read_from<R: BufRead>(mut reader: R) -> Result<(Self, u64)> {
+        let mut line_count = 0;
+        let mut data_offset = 0;
+        let meta = crate::utils::load_meta(&mut reader, &mut line_count, &mut data_offset)?;
+        Ok((meta, data_offset))
+    }
+
+    /// Writes the PCD header for the meta data.
+    ///
+    /// Reading the header back gives the same meta data.
+    pub fn write_header<W: Write>(&self, mut writer: W) -> Result<()> {
+        let width_arg_width = self.width.to_string().len();
+        let points_arg_width = self.num_points.to_string().len();
+        crate::utils::write_meta(
+            &mut writer,
+            self,
+            width_arg_width,
+            points_arg_width,
+            Default::default(),
+        )?;
+        Ok(())
+    }
+
+    /// Parses the PCD header of a file without reading the point data.
+    ///
+    /// It returns the meta data and the byte offset where the point
+    /// data starts. For binary data, it fails with
+    /// [TruncatedDataError](Error::TruncatedDataError) if the file is
+    /// too short to contain all points, or with
+    /// [ParseError](Error::ParseError) if the data size overflows.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<(Self, u64)> {
+        let file = File::open(path.as_ref())?;
+        let file_size = file.metadata()?.len();
+        let mut line_count = 0;
+        let mut data_offset = 0;
+        let meta =
+            crate::utils::load_meta(&mut BufReader::new(file), &mut line_count, &mut data_offset)?;
+
+        if meta.data == DataKind::Binary {
+            let expect = meta
+                .num_points
+                .checked_mul(meta.field_defs.record_size_bytes() as u64)
+                .ok_or_else(|| {
+                    Error::new_parse_error(line_count, "POINTS and SIZE entries overflow data size")
+                })?;
+            let found = file_size - data_offset;
+            if found < expect {
+                return Err(Error::new_truncated_data_error(expect, found).into());
+            }
+        }
+
+        Ok((meta, data_offset))
+    }
+}
+
 /// Represents VIEWPOINT field in meta data.
 #[derive(Debug, Clone, PartialEq)]
 pub struct ViewPoint {
@@ -68,12 +146,84 @@ pub enum ValueKind {
     F64,
 }
 
+impl ValueKind {
+    /// Gets the value kind from the TYPE and SIZE entries in PCD header.
+    pub fn from_type_size(type_kind: TypeKind, size: u64) -> Option<Self> {
+        use TypeKind as T;
+        use ValueKind as K;
+
+        let kind = match (type_kind, size) {
+            (T::U, 1) => K::U8,
+            (T::U, 2) => K::U16,
+            (T::U, 4) => K::U32,
+            (T::I, 1) => K::I8,
+            (T::I, 2) => K::I16,
+            (T::I, 4) => K::I32,
+            (T::F, 4) => K::F32,
+            (T::F, 8) => K::F64,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    /// Gets the size of a value in bytes.
+    pub const fn size(&self) -> usize {
+        use ValueKind::*;
+
+        match self {
+            U8 | I8 => 1,
+            U16 | I16 => 2,
+            U32 | I32 | F32 => 4,
+            F64 => 8,
+        }
+    }
+
+    /// Gets the type kind written in TYPE entry of PCD header.
+    pub fn type_kind(&self) -> TypeKind {
+        use ValueKind::*;
+
+        match self {
+            U8 | U16 | U32 => TypeKind::U,
+            I8 | I16 | I32 => TypeKind::I,
+            F32 | F64 => TypeKind::F,
+        }
+    }
+}
+
 /// Define the properties of a PCD field.
+///
+/// The `offset` is the byte offset of the field within a binary
+/// record. Offsets in a [Schema] always follow the field order without
+/// gaps, where gaps are represented by padding fields. Padding fields
+/// are named `_` in the PCD header. They are skipped when records are
+/// read and are filled with zeros when records are written.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FieldDef {
     pub name: String,
     pub kind: ValueKind,
     pub count: u64,
+    pub offset: u64,
+    pub padding: bool,
+}
+
+impl FieldDef {
+    /// The name of padding fields in PCD header.
+    pub const PADDING_NAME: &'static str = "_";
+
+    /// Gets the size of the field in bytes.
+    pub fn size(&self) -> usize {
+        self.kind.size() * self.count as usize
+    }
+
+    fn new_padding(offset: u64, size: u64) -> Self {
+        Self {
+            name: Self::PADDING_NAME.to_string(),
+            kind: ValueKind::U8,
+            count: size,
+            offset,
+            padding: true,
+        }
+    }
 }
 
 /// Define the schema of PCD format.
@@ -83,6 +233,103 @@ pub struct Schema {
 }
 
 impl Schema {
+    /// Creates a [SchemaBuilder] to build a validated schema.
+    pub fn builder() -> SchemaBuilder {
+        SchemaBuilder::new()
+    }
+
+    /// Builds a schema from fields with explicit byte offsets.
+    ///
+    /// The items are `(name, kind, count, offset)` tuples. The gaps
+    /// between fields and the trailing bytes up to `record_size` are
+    /// filled with padding fields.
+    pub fn from_offsets<S, I>(fields: I, record_size: u64) -> Result<Self>
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = (S, ValueKind, u64, u64)>,
+    {
+        let mut fields: Vec<_> = fields
+            .into_iter()
+            .map(|(name, kind, count, offset)| (name.into(), kind, count, offset))
+            .collect();
+        fields.sort_by_key(|&(_, _, _, offset)| offset);
+
+        fields
+            .into_iter()
+            .fold(
+                SchemaBuilder::new(),
+                |builder, (name, kind, count, offset)| builder.field_at(name, kind, count, offset),
+            )
+            .record_size(record_size)
+            .build()
+    }
+
+    /// Checks that field names are non-empty and unique, counts are
+    /// non-zero and fields are laid out contiguously by their offsets.
+    pub fn validate(&self) -> Result<()> {
+        let mut names = HashSet::new();
+        let mut end = 0;
+
+        for field in self.fields.iter() {
+            if field.offset != end {
+                return Err(Error::new_field_offset_error(&field.name, field.offset, end).into());
+            }
+            end += field.size() as u64;
+
+            if field.count == 0 {
+                return Err(Error::new_zero_count_error(&field.name).into());
+            }
+            if field.padding {
+                continue;
+            }
+            if field.name.is_empty() {
+                return Err(Error::EmptyFieldNameError.into());
+            }
+            if !names.insert(field.name.as_str()) {
+                return Err(Error::new_duplicate_field_error(&field.name).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the non-padding field with the name.
+    pub fn field(&self, name: &str) -> Option<&FieldDef> {
+        self.fields
+            .iter()
+            .find(|field| !field.padding && field.name == name)
+    }
+
+    /// Gets the index of the non-padding field with the name.
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.fields
+            .iter()
+            .position(|field| !field.padding && field.name == name)
+    }
+
+    /// Gets the byte offset of the non-padding field with the name.
+    pub fn offset_of(&self, name: &str) -> Option<u64> {
+        Some(self.field(name)?.offset)
+    }
+
+    /// Gets the size of a binary record in bytes, including padding.
+    pub fn record_size_bytes(&self) -> usize {
+        self.fields.iter().map(|field| field.size()).sum()
+    }
+
+    /// Returns true if the schema contains padding fields.
+    pub fn has_padding(&self) -> bool {
+        self.fields.iter().any(|field| field.padding)
+    }
+
+    /// Returns a schema with padding fields removed.
+    ///
+    /// The offsets of the remaining fields are recomputed so that the
+    /// fields are packed.
+    pub fn without_padding(&self) -> Schema {
+        self.fields.iter().filter(|field| !field.padding).collect()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.fields.is_empty()
     }
@@ -96,6 +343,112 @@ impl Schema {
     }
 }
 
+/// A builder type that builds a validated [Schema].
+///
+/// Fields are appended in order. A field added by
+/// [field](SchemaBuilder::field) directly follows the previous field,
+/// while [field_at](SchemaBuilder::field_at) places the field at an
+/// explicit offset and fills the gap with padding.
+///
+/// ```rust
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// use pcd_rs::{Schema, ValueKind};
+///
+/// let schema = Schema::builder()
+///     .field("x", ValueKind::F32, 1)
+///     .field("y", ValueKind::F32, 1)
+///     .field("z", ValueKind::F32, 1)
+///     .field_at("intensity", ValueKind::F32, 1, 16)
+///     .build()?;
+///
+/// assert_eq!(schema.offset_of("intensity"), Some(16));
+/// assert_eq!(schema.record_size_bytes(), 20);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SchemaBuilder {
+    fields: Vec<(String, ValueKind, u64, Option<u64>)>,
+    record_size: Option<u64>,
+}
+
+impl SchemaBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a field right after the previous field.
+    pub fn field(mut self, name: impl Into<String>, kind: ValueKind, count: u64) -> Self {
+        self.fields.push((name.into(), kind, count, None));
+        self
+    }
+
+    /// Appends a field at the byte offset.
+    pub fn field_at(
+        mut self,
+        name: impl Into<String>,
+        kind: ValueKind,
+        count: u64,
+        offset: u64,
+    ) -> Self {
+        self.fields.push((name.into(), kind, count, Some(offset)));
+        self
+    }
+
+    /// Appends a padding field of `size` bytes.
+    pub fn padding(self, size: u64) -> Self {
+        self.field(FieldDef::PADDING_NAME, ValueKind::U8, size)
+    }
+
+    /// Sets the record size in bytes. Trailing bytes after the last
+    /// field are filled with padding.
+    pub fn record_size(mut self, size: u64) -> Self {
+        self.record_size = Some(size);
+        self
+    }
+
+    /// Validates the fields and builds the schema.
+    pub fn build(self) -> Result<Schema> {
+        let mut fields = vec![];
+        let mut end = 0;
+
+        for (name, kind, count, offset) in self.fields {
+            let offset = offset.unwrap_or(end);
+            if offset < end {
+                return Err(Error::new_field_offset_error(&name, offset, end).into());
+            }
+            if offset > end {
+                fields.push(FieldDef::new_padding(end, offset - end));
+            }
+
+            let padding = name == FieldDef::PADDING_NAME;
+            let field = FieldDef {
+                name,
+                kind,
+                count,
+                offset,
+                padding,
+            };
+            end = offset + field.size() as u64;
+            fields.push(field);
+        }
+
+        if let Some(record_size) = self.record_size {
+            if end > record_size {
+                return Err(Error::new_record_size_error(end, record_size).into());
+            }
+            if record_size > end {
+                fields.push(FieldDef::new_padding(end, record_size - end));
+            }
+        }
+
+        let schema = Schema { fields };
+        schema.validate()?;
+        Ok(schema)
+    }
+}
+
 impl Index<usize> for Schema {
     type Output = FieldDef;
 
@@ -122,11 +475,25 @@ impl<'a> IntoIterator for &'a Schema {
     }
 }
 
+/// Collects fields in order, where the offsets are recomputed so that
+/// the fields are packed.
 impl FromIterator<(String, ValueKind, u64)> for Schema {
     fn from_iter<T: IntoIterator<Item = (String, ValueKind, u64)>>(iter: T) -> Self {
+        let mut offset = 0;
         let fields = iter
             .into_iter()
-            .map(|(name, kind, count)| FieldDef { name, kind, count })
+            .map(|(name, kind, count)| {
+                let padding = name == FieldDef::PADDING_NAME;
+                let field = FieldDef {
+                    name,
+                    kind,
+                    count,
+                    offset,
+                    padding,
+                };
+                offset += kind.size() as u64 * count;
+                field
+            })
             .collect();
         Self { fields }
     }
@@ -140,18 +507,25 @@ impl<'a> FromIterator<(&'a str, ValueKind, u64)> for Schema {
     }
 }
 
+/// Collects fields in order, where the offsets are recomputed so that
+/// the fields are packed.
 impl FromIterator<FieldDef> for Schema {
     fn from_iter<T: IntoIterator<Item = FieldDef>>(iter: T) -> Self {
-        Self {
-            fields: iter.into_iter().collect(),
-        }
+        let mut offset = 0;
+        let fields = iter
+            .into_iter()
+            .map(|field| {
+                let field = FieldDef { offset, ..field };
+                offset += field.size() as u64;
+                field
+            })
+            .collect();
+        Self { fields }
     }
 }
 
 impl<'a> FromIterator<&'a FieldDef> for Schema {
     fn from_iter<T: IntoIterator<Item = &'a FieldDef>>(iter: T) -> Self {
-        Self {
-            fields: iter.into_iter().map(|field| field.to_owned()).collect(),
-        }
+        iter.into_iter().cloned().collect()
     }
 }
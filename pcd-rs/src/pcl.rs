@@ -0,0 +1,83 @@
+//! Point types compatible with PCL.
+//!
+//! The types have the same field names, types and counts as the PCL
+//! point types of the same names, so that PCD files written by PCL load
+//! into them, and PCL loads the files written with them. The packed
+//! `rgb` and `rgba` fields are converted by [codec](crate::codec).
+//!
+//! ```rust
+//! # use anyhow::Result;
+//! # fn main() -> Result<()> {
+//! use pcd_rs::{pcl::PointXYZRGB, Reader};
+//!
+//! let reader = Reader::<PointXYZRGB, _>::open("test_files/ascii.pcd")?;
+//! let points: Result<Vec<_>> = reader.collect();
+//! assert_eq!(points?[0].rgb, [64, 64, 112]);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{PcdDeserialize, PcdSchema, PcdSerialize};
+
+/// A point with `x y z` fields.
+#[derive(Debug, Clone, Copy, PartialEq, Default, PcdDeserialize, PcdSerialize, PcdSchema)]
+pub struct PointXYZ {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+/// A point with `x y z intensity` fields.
+#[derive(Debug, Clone, Copy, PartialEq, Default, PcdDeserialize, PcdSerialize, PcdSchema)]
+pub struct PointXYZI {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub intensity: f32,
+}
+
+/// A point with `x y z rgb` fields. The color is packed in a `F32` field.
+#[derive(Debug, Clone, Copy, PartialEq, Default, PcdDeserialize, PcdSerialize, PcdSchema)]
+pub struct PointXYZRGB {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    #[pcd(with = "crate::codec::rgb")]
+    pub rgb: [u8; 3],
+}
+
+/// A point with `x y z rgba` fields. The color is packed in a `U32` field.
+#[derive(Debug, Clone, Copy, PartialEq, Default, PcdDeserialize, PcdSerialize, PcdSchema)]
+pub struct PointXYZRGBA {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    #[pcd(with = "crate::codec::rgba")]
+    pub rgba: [u8; 4],
+}
+
+/// A point with `x y z normal_x normal_y normal_z curvature` fields.
+#[derive(Debug, Clone, Copy, PartialEq, Default, PcdDeserialize, PcdSerialize, PcdSchema)]
+pub struct PointNormal {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub normal_x: f32,
+    pub normal_y: f32,
+    pub normal_z: f32,
+    pub curvature: f32,
+}
+
+/// A point with `x y z rgb normal_x normal_y normal_z curvature` fields.
+#[derive(Debug, Clone, Copy, PartialEq, Default, PcdDeserialize, PcdSerialize, PcdSchema)]
+pub struct PointXYZRGBNormal {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    #[pcd(with = "crate::codec::rgb")]
+    pub rgb: [u8; 3],
+    pub normal_x: f32,
+    pub normal_y: f32,
+    pub normal_z: f32,
+    pub curvature: f32,
+}
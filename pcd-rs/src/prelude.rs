@@ -1,3 +1,6 @@
 //! The Prelude for pcd-rs, including commonly used traits.
 
-pub use crate::record::{PcdDeserialize, PcdSerialize};
+pub use crate::{
+    record::{PcdColumns, PcdDeserialize, PcdSchema, PcdSerialize},
+    traits::PcdValue,
+};
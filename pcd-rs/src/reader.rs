@@ -32,8 +32,9 @@ fn main() -> Result<()> {
 
 use crate::{
     error::Error,
-    metas::{DataKind, FieldDef, PcdMeta},
-    record::{DynRecord, PcdDeserialize},
+    metas::{DataKind, PcdMeta, Schema},
+    record::{DynRecord, PcdColumns, PcdDeserialize},
+    text::Tokens,
 };
 use anyhow::Result;
 use std::{
@@ -52,9 +53,16 @@ where
     R: Read,
 {
     meta: PcdMeta,
+    record_spec: Schema,
+    /// The variant of enum records picked for the schema
+    variant: usize,
     record_count: usize,
     finished: bool,
     reader: R,
+    skip_invalid: bool,
+    xyz_fields: Vec<usize>,
+    buffer: Vec<u8>,
+    record_buffer: Vec<u8>,
     _phantom: PhantomData<T>,
 }
 
@@ -75,50 +83,32 @@ where
 {
     pub fn from_reader(mut reader: R) -> Result<Self> {
         let mut line_count = 0;
-        let meta = crate::utils::load_meta(&mut reader, &mut line_count)?;
+        let mut byte_count = 0;
+        let meta = crate::utils::load_meta(&mut reader, &mut line_count, &mut byte_count)?;
 
-        // Checks whether the record schema matches the file meta
-        if !Record::is_dynamic() {
-            let record_spec = Record::read_spec();
-
-            let mismatch_error =
-                Error::new_schema_mismatch_error(record_spec.as_slice(), &meta.field_defs.fields);
-
-            if record_spec.len() != meta.field_defs.len() {
-                return Err(mismatch_error.into());
-            }
+        // Padding fields are not visible to records
+        let record_spec = meta.field_defs.without_padding();
 
-            for (record_field, meta_field) in record_spec.into_iter().zip(meta.field_defs.iter()) {
-                let (name_opt, record_kind, record_count_opt) = record_field;
-                let FieldDef {
-                    name: meta_name,
-                    kind: meta_kind,
-                    count: meta_count,
-                } = meta_field;
-
-                if record_kind != *meta_kind {
-                    return Err(mismatch_error.into());
-                }
+        // Checks whether the record schema matches the file meta
+        let variant = if Record::is_dynamic() {
+            0
+        } else {
+            Record::variant_index(&record_spec)?
+        };
 
-                if let Some(name) = &name_opt {
-                    if name != meta_name {
-                        return Err(mismatch_error.into());
-                    }
-                }
-
-                if let Some(record_count) = record_count_opt {
-                    if record_count != *meta_count as usize {
-                        return Err(mismatch_error.into());
-                    }
-                }
-            }
-        }
+        let xyz_fields = crate::utils::find_xyz_fields(&meta.field_defs);
 
         let pcd_reader = Reader {
             meta,
+            record_spec,
             reader,
+            variant,
             record_count: 0,
             finished: false,
+            skip_invalid: false,
+            xyz_fields,
+            buffer: vec![],
+            record_buffer: vec![],
             _phantom: PhantomData,
         };
 
@@ -146,40 +136,288 @@ where
     }
 }
 
-impl<R, Record> Iterator for Reader<Record, R>
+impl<R, Record> Reader<Record, R>
 where
     R: BufRead,
     Record: PcdDeserialize,
 {
-    type Item = Result<Record>;
+    /// Reads the bytes of a binary record or the line of an Ascii
+    /// record into the buffer.
+    fn read_raw_record(&mut self) -> Result<()> {
+        self.buffer.clear();
 
-    fn next(&mut self) -> Option<Self::Item> {
+        match self.meta.data {
+            DataKind::Binary => {
+                let record_size = self.meta.field_defs.record_size_bytes();
+                self.buffer.resize(record_size, 0);
+                self.reader.read_exact(&mut self.buffer)?;
+            }
+            DataKind::Ascii => {
+                self.reader.read_until(b'\n', &mut self.buffer)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks if the x, y and z values in the buffer are finite.
+    fn is_raw_record_finite(&self) -> bool {
+        match self.meta.data {
+            DataKind::Binary => {
+                crate::utils::is_chunk_finite(&self.buffer, &self.meta.field_defs, &self.xyz_fields)
+            }
+            DataKind::Ascii => {
+                crate::utils::is_line_finite(&self.buffer, &self.meta.field_defs, &self.xyz_fields)
+            }
+        }
+    }
+
+    /// Decodes the record in the buffer while skipping the padding fields.
+    ///
+    /// Padding bytes are dropped from binary records. Ascii records
+    /// have `count` tokens for each padding field like PCL does, and
+    /// these tokens are dropped.
+    fn decode_raw_record(&mut self) -> Result<Record> {
+        let variant = self.variant;
+        self.decode_raw_record_with(|data_kind, data, spec| match data_kind {
+            DataKind::Binary => Record::read_variant_chunk(data, spec, variant),
+            DataKind::Ascii => Record::read_variant_line(data, spec, variant),
+        })
+    }
+
+    /// Passes the record in the buffer without padding fields to `decode`.
+    fn decode_raw_record_with<T, F>(&mut self, decode: F) -> Result<T>
+    where
+        F: FnOnce(DataKind, &mut &[u8], &Schema) -> Result<T>,
+    {
+        if !self.meta.field_defs.has_padding() {
+            return decode(
+                self.meta.data,
+                &mut self.buffer.as_slice(),
+                &self.record_spec,
+            );
+        }
+
+        let data = &mut self.record_buffer;
+        data.clear();
+
+        match self.meta.data {
+            DataKind::Binary => {
+                for def in self.meta.field_defs.iter().filter(|def| !def.padding) {
+                    let begin = def.offset as usize;
+                    let end = begin + def.size();
+                    data.extend_from_slice(&self.buffer[begin..end]);
+                }
+            }
+            DataKind::Ascii => {
+                let mut tokens = Tokens::new(&self.buffer);
+
+                for def in self.meta.field_defs.iter() {
+                    let field_tokens = tokens.by_ref().take(def.count as usize);
+                    if def.padding {
+                        field_tokens.for_each(drop);
+                    } else {
+                        field_tokens.for_each(|token| {
+                            data.extend_from_slice(token);
+                            data.push(b' ');
+                        });
+                    }
+                }
+                // Leave trailing tokens for the token count check
+                tokens.for_each(|token| {
+                    data.extend_from_slice(token);
+                    data.push(b' ');
+                });
+            }
+        }
+
+        decode(self.meta.data, &mut data.as_slice(), &self.record_spec)
+    }
+
+    /// Drops points with non-finite x, y or z values while reading.
+    ///
+    /// By default, invalid points are kept as NaN placeholders like
+    /// PCL does, so that points in organized clouds are found at
+    /// `row * width + col`. It fails if none of x, y and z fields exist.
+    pub fn skip_invalid(mut self) -> Result<Self> {
+        if self.xyz_fields.is_empty() {
+            return Err(Error::new_field_not_found_error("x").into());
+        }
+        self.skip_invalid = true;
+        Ok(self)
+    }
+
+    /// Checks if the x, y and z values of all remaining points are
+    /// finite, which is the `is_dense` flag in PCL.
+    pub fn is_dense(mut self) -> Result<bool> {
+        while self.record_count < self.meta.num_points as usize {
+            self.read_raw_record()?;
+            self.record_count += 1;
+
+            if !self.is_raw_record_finite() {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<R, Record> Reader<Record, R>
+where
+    R: BufRead,
+    Record: PcdDeserialize,
+{
+    /// Reads at most `max_records` points and appends them to `records`.
+    ///
+    /// It returns the number of read points, which is zero when all
+    /// points are read. Binary records without padding fields are read
+    /// by [read_chunks()](PcdDeserialize::read_chunks) in one block.
+    pub fn read_block(&mut self, records: &mut Vec<Record>, max_records: usize) -> Result<usize> {
         if self.finished {
-            return None;
+            return Ok(0);
         }
 
-        let record_result = match self.meta.data {
-            DataKind::Ascii => Record::read_line(&mut self.reader, &self.meta.field_defs),
-            DataKind::Binary => Record::read_chunk(&mut self.reader, &self.meta.field_defs),
-        };
+        let count = max_records.min(self.meta.num_points as usize - self.record_count);
 
-        match record_result {
-            Ok(_) => {
-                self.record_count += 1;
-                if self.record_count == self.meta.num_points as usize {
-                    self.finished = true;
+        if self.meta.data == DataKind::Binary
+            && !self.meta.field_defs.has_padding()
+            && !self.skip_invalid
+        {
+            let result = Record::read_variant_chunks(
+                &mut self.reader,
+                &self.record_spec,
+                self.variant,
+                count,
+                records,
+            );
+            if let Err(error) = result {
+                self.finished = true;
+                return Err(error);
+            }
+
+            self.record_count += count;
+            if self.record_count == self.meta.num_points as usize {
+                self.finished = true;
+            }
+            Ok(count)
+        } else {
+            let len = records.len();
+            records.reserve(count);
+            for _ in 0..count {
+                match self.next() {
+                    Some(record) => records.push(record?),
+                    None => break,
                 }
             }
-            Err(_) => {
+            Ok(records.len() - len)
+        }
+    }
+}
+
+impl<R, Record> Reader<Record, R>
+where
+    R: BufRead,
+    Record: PcdDeserialize,
+{
+    /// Reads all remaining points into the columns.
+    ///
+    /// It returns the number of read points. The values are decoded
+    /// into the columns directly without building records.
+    pub fn read_columns<C>(&mut self, columns: &mut C) -> Result<usize>
+    where
+        C: PcdColumns<Record = Record>,
+    {
+        let len = columns.len();
+
+        while !self.finished && self.record_count < self.meta.num_points as usize {
+            let result = self.read_column_record(columns);
+            self.record_count += 1;
+
+            if let Err(error) = result {
                 self.finished = true;
+                return Err(error);
             }
         }
+        self.finished = true;
 
-        Some(record_result)
+        Ok(columns.len() - len)
+    }
+
+    fn read_column_record<C: PcdColumns>(&mut self, columns: &mut C) -> Result<()> {
+        if !self.skip_invalid && !self.meta.field_defs.has_padding() {
+            return match self.meta.data {
+                DataKind::Ascii => columns.read_line(&mut self.reader, &self.record_spec),
+                DataKind::Binary => columns.read_chunk(&mut self.reader, &self.record_spec),
+            };
+        }
+
+        self.read_raw_record()?;
+        if self.skip_invalid && !self.is_raw_record_finite() {
+            return Ok(());
+        }
+        self.decode_raw_record_with(|data_kind, data, spec| match data_kind {
+            DataKind::Binary => columns.read_chunk(data, spec),
+            DataKind::Ascii => columns.read_line(data, spec),
+        })
+    }
+}
+
+impl<R, Record> Iterator for Reader<Record, R>
+where
+    R: BufRead,
+    Record: PcdDeserialize,
+{
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.finished {
+                return None;
+            }
+
+            let record_result = if self.skip_invalid {
+                match self.read_raw_record() {
+                    Ok(()) if !self.is_raw_record_finite() => None,
+                    Ok(()) => Some(self.decode_raw_record()),
+                    Err(error) => Some(Err(error)),
+                }
+            } else if self.meta.field_defs.has_padding() {
+                Some(
+                    self.read_raw_record()
+                        .and_then(|()| self.decode_raw_record()),
+                )
+            } else {
+                let (reader, spec) = (&mut self.reader, &self.record_spec);
+                Some(match self.meta.data {
+                    DataKind::Ascii => Record::read_variant_line(reader, spec, self.variant),
+                    DataKind::Binary => Record::read_variant_chunk(reader, spec, self.variant),
+                })
+            };
+
+            match record_result {
+                Some(Err(_)) => {
+                    self.finished = true;
+                }
+                _ => {
+                    self.record_count += 1;
+                    if self.record_count == self.meta.num_points as usize {
+                        self.finished = true;
+                    }
+                }
+            }
+
+            if record_result.is_some() {
+                return record_result;
+            }
+        }
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
         let size = self.meta.num_points as usize;
-        (size, Some(size))
+        if self.skip_invalid {
+            (0, Some(size - self.record_count))
+        } else {
+            (size, Some(size))
+        }
     }
 }
@@ -31,9 +31,12 @@ The derive macro accepts normal structs and tuple structs, but does not accept u
 [PcdDeserialize](crate::record::PcdDeserialize) allows fields with either primitive type,
 array of primitive type or [Vec](<std::vec::Vec>) of primitive type.
 
-[PcdSerialize](crate::record::PcdSerialize) allows fields with either primitive type or
-array of primitive type. The [Vec](<std::vec::Vec>) is ruled out since the length
-is not determined in compile-time.
+Types implementing [PcdValue](crate::traits::PcdValue), like `bool` and derived
+fieldless enums, are accepted in place of primitive types.
+
+[PcdSerialize](crate::record::PcdSerialize) allows the same field types. The length of a
+[Vec](<std::vec::Vec>) field is set by `#[pcd(count = N)]`, or otherwise by the schema
+given to [WriterInit](crate::WriterInit). The length is checked on every push.
 
 Make sure struct field names match the `FIELDS` header in PCD data.
 Otherwise it panics at runtime. You can specify the exact name in header or bypass name check
@@ -58,15 +61,72 @@ pub struct TimestampedPoint {
 ```
 "##
 )]
+#![doc = r##"
+Structs with `#[repr(C)]` and `#[pcd(pod)]` attributes are read and
+written as raw blocks of bytes in binary mode. The fields must be
+primitive or array of primitive types without padding bytes between
+them, which is checked in compile-time.
+"##]
+#![cfg_attr(
+    feature = "derive",
+    doc = r##"
+```rust
+use pcd_rs::{PcdDeserialize, PcdSerialize};
+
+#[derive(PcdDeserialize, PcdSerialize)]
+#[repr(C)]
+#[pcd(pod)]
+pub struct Point {
+    x: f32,
+    y: f32,
+    z: f32,
+    rgb: u32,
+}
+```
+"##
+)]
+#![doc = r##"
+Tuples of up to 12 elements and arrays `[T; N]` are records with a
+field for each element, where the element types implement
+[StoredValue](crate::codec::StoredValue). The fields are matched by
+position when read. They are written as `x`, `y`, `z`, and then
+`field_N`, unless the schema given to the writer names them.
+
+```rust
+# use anyhow::Result;
+# fn main() -> Result<()> {
+use pcd_rs::{DataKind, Reader, WriterInit};
+
+let points = vec![(1.0f32, 2.0f32, 3.0f32), (4.0, 5.0, 6.0)];
+let mut writer = WriterInit {
+    width: points.len() as u64,
+    data_kind: DataKind::Ascii,
+    ..Default::default()
+}
+.create("test_files/dump_tuple_doc.pcd")?;
+writer.push_all(&points)?;
+writer.finish()?;
+
+let reader = Reader::<[f32; 3], _>::open("test_files/dump_tuple_doc.pcd")?;
+let arrays = reader.collect::<Result<Vec<_>>>()?;
+assert_eq!(arrays, [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+# std::fs::remove_file("test_files/dump_tuple_doc.pcd")?;
+# Ok(())
+# }
+```
+"##]
 use crate::{
+    codec::StoredValue,
     error::Error,
     metas::{FieldDef, Schema, ValueKind},
+    text::{scan_line, FromToken, Tokens},
     traits::Value,
+    writer::FloatFormat,
 };
 use anyhow::{bail, Result};
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use num_traits::NumCast;
-use std::io::prelude::*;
+use std::{borrow::Cow, io::prelude::*};
 
 /// [PcdDeserialize](crate::record::PcdDeserialize) is analogous to a _point_ returned from a reader.
 ///
@@ -76,10 +136,189 @@ use std::io::prelude::*;
 /// When the PCD data is in Ascii mode, the record is represented by a line of literals.
 /// Otherwise if the data is in binary mode, the record is represented by a fixed size chunk.
 pub trait PcdDeserialize: Sized {
+    /// The fields read by the record if their names, kinds and counts
+    /// are fixed. The derive sets it unless the record has ignored,
+    /// default, unknown-skipping or uncounted `Vec` fields.
+    const READ_FIELDS: Option<&'static [StaticFieldDef]> = None;
+
     fn is_dynamic() -> bool;
     fn read_spec() -> Vec<(Option<String>, ValueKind, Option<usize>)>;
+
+    /// Checks if the record type can be read from data with the schema.
+    /// The default implementation compares the schema with
+    /// [READ_FIELDS](PcdDeserialize::READ_FIELDS) if it is set, or
+    /// [read_spec()](PcdDeserialize::read_spec) field by field.
+    fn check_schema(field_defs: &Schema) -> Result<()> {
+        match Self::READ_FIELDS {
+            Some(fields) if matches_static_fields(fields, field_defs) => Ok(()),
+            Some(_) => {
+                Err(Error::new_schema_mismatch_error(&Self::read_spec(), &field_defs.fields).into())
+            }
+            None => check_read_spec(&Self::read_spec(), &[], false, field_defs),
+        }
+    }
     fn read_chunk<R: BufRead>(reader: &mut R, field_defs: &Schema) -> Result<Self>;
     fn read_line<R: BufRead>(reader: &mut R, field_defs: &Schema) -> Result<Self>;
+
+    /// Reads `count` records in binary mode and appends them to
+    /// `records`. The default implementation reads the records one by one.
+    fn read_chunks<R: BufRead>(
+        reader: &mut R,
+        field_defs: &Schema,
+        count: usize,
+        records: &mut Vec<Self>,
+    ) -> Result<()> {
+        records.reserve(count);
+        for _ in 0..count {
+            records.push(Self::read_chunk(reader, field_defs)?);
+        }
+        Ok(())
+    }
+
+    /// Checks the schema and gets the variant to read. Enum records
+    /// pick the variant once here, and readers pass it to the
+    /// `read_variant_*` methods. Other records have one variant.
+    #[doc(hidden)]
+    fn variant_index(field_defs: &Schema) -> Result<usize> {
+        Self::check_schema(field_defs)?;
+        Ok(0)
+    }
+
+    #[doc(hidden)]
+    fn read_variant_chunk<R: BufRead>(
+        reader: &mut R,
+        field_defs: &Schema,
+        _variant: usize,
+    ) -> Result<Self> {
+        Self::read_chunk(reader, field_defs)
+    }
+
+    #[doc(hidden)]
+    fn read_variant_line<R: BufRead>(
+        reader: &mut R,
+        field_defs: &Schema,
+        _variant: usize,
+    ) -> Result<Self> {
+        Self::read_line(reader, field_defs)
+    }
+
+    #[doc(hidden)]
+    fn read_variant_chunks<R: BufRead>(
+        reader: &mut R,
+        field_defs: &Schema,
+        _variant: usize,
+        count: usize,
+        records: &mut Vec<Self>,
+    ) -> Result<()> {
+        Self::read_chunks(reader, field_defs, count, records)
+    }
+}
+
+/// Checks the read spec of a record type against the schema.
+///
+/// The fields in the schema are matched with the read spec in order.
+/// A field marked in `defaults` is skipped if the next field in the
+/// schema has another name, so that the record fills a default value.
+/// If `skip_unknown` is set, named fields are searched forward in the
+/// schema, and the fields in between or left over are skipped.
+#[doc(hidden)]
+pub fn check_read_spec(
+    read_spec: &[(Option<String>, ValueKind, Option<usize>)],
+    defaults: &[bool],
+    skip_unknown: bool,
+    field_defs: &Schema,
+) -> Result<()> {
+    let mismatch_error = || Error::new_schema_mismatch_error(read_spec, &field_defs.fields);
+    let mut field_index = 0;
+
+    for (index, (name_opt, kind, count_opt)) in read_spec.iter().enumerate() {
+        let has_default = defaults.get(index).copied().unwrap_or(false);
+        let found = match name_opt {
+            Some(name) if skip_unknown => find_field(field_defs, field_index, name),
+            Some(name) if has_default => field_defs
+                .fields
+                .get(field_index)
+                .filter(|def| &def.name == name)
+                .map(|_| field_index),
+            _ => (field_index < field_defs.len()).then_some(field_index),
+        };
+
+        let Some(found) = found else {
+            if has_default {
+                continue;
+            }
+            return Err(mismatch_error().into());
+        };
+
+        let FieldDef {
+            name: meta_name,
+            kind: meta_kind,
+            count: meta_count,
+            ..
+        } = &field_defs.fields[found];
+
+        let is_name_ok = name_opt.iter().all(|name| name == meta_name);
+        let is_count_ok = count_opt.iter().all(|&count| count == *meta_count as usize);
+        if !is_name_ok || kind != meta_kind || !is_count_ok {
+            return Err(mismatch_error().into());
+        }
+        field_index = found + 1;
+    }
+
+    if !skip_unknown && field_index < field_defs.len() {
+        return Err(mismatch_error().into());
+    }
+
+    Ok(())
+}
+
+/// Finds the index of the named field in the schema from `from`.
+#[doc(hidden)]
+pub fn find_field(field_defs: &Schema, from: usize, name: &str) -> Option<usize> {
+    let position = field_defs
+        .fields
+        .get(from..)?
+        .iter()
+        .position(|def| def.name == name)?;
+    Some(from + position)
+}
+
+/// Skips the values of the fields in a binary record.
+#[doc(hidden)]
+pub fn skip_chunk_fields<R: BufRead>(reader: &mut R, defs: &[FieldDef]) -> Result<()> {
+    let size: usize = defs.iter().map(|def| def.size()).sum();
+    let skipped = std::io::copy(&mut reader.take(size as u64), &mut std::io::sink())?;
+    if skipped != size as u64 {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+    Ok(())
+}
+
+/// Skips the tokens of the fields in an Ascii record.
+#[doc(hidden)]
+pub fn skip_line_fields(tokens: &mut Tokens<'_>, defs: &[FieldDef]) -> Result<()> {
+    let count: usize = defs.iter().map(|def| def.count as usize).sum();
+    let skipped = tokens.take(count).count();
+    if skipped != count {
+        return Err(Error::new_text_token_mismatch_error(count, skipped).into());
+    }
+    Ok(())
+}
+
+/// Gets the index of the first variant whose schema check passes, or
+/// the error of the first check.
+#[doc(hidden)]
+pub fn select_variant(checks: &[fn(&Schema) -> Result<()>], field_defs: &Schema) -> Result<usize> {
+    let mut first_error = None;
+    for (index, check) in checks.iter().enumerate() {
+        match check(field_defs) {
+            Ok(()) => return Ok(index),
+            Err(error) => {
+                first_error.get_or_insert(error);
+            }
+        }
+    }
+    Err(first_error.unwrap_or_else(|| Error::new_invalid_argument_error("no variants").into()))
 }
 
 /// [PcdSerialize](crate::record::PcdSerialize) is analogous to a _point_ written by a writer.
@@ -90,10 +329,153 @@ pub trait PcdDeserialize: Sized {
 /// When the PCD data is in Ascii mode, the record is represented by a line of literals.
 /// Otherwise if the data is in binary mode, the record is represented by a fixed size chunk.
 pub trait PcdSerialize: Sized {
+    /// The fields written by the record if their names, kinds and
+    /// counts are fixed. The derive sets it unless the record has
+    /// uncounted `Vec` fields.
+    const WRITE_FIELDS: Option<&'static [StaticFieldDef]> = None;
+
     fn is_dynamic() -> bool;
+
+    /// Gets the schema of the record. A field has zero count if its
+    /// length is determined by the schema given to the writer.
     fn write_spec() -> Schema;
+
+    /// Returns true if the fields are written by position, so that the
+    /// schema given to the writer may name them differently from
+    /// [write_spec()](PcdSerialize::write_spec).
+    fn is_positional() -> bool {
+        false
+    }
     fn write_chunk<R: Write + Seek>(&self, writer: &mut R, spec: &Schema) -> Result<()>;
     fn write_line<R: Write + Seek>(&self, writer: &mut R, spec: &Schema) -> Result<()>;
+
+    /// Writes the record in Ascii mode, where `formats` gives the float
+    /// format of each field. Fields without a format use the default.
+    /// The default implementation ignores `formats` and calls
+    /// [write_line()](PcdSerialize::write_line).
+    fn write_line_with<R: Write + Seek>(
+        &self,
+        writer: &mut R,
+        spec: &Schema,
+        _formats: &[FloatFormat],
+    ) -> Result<()> {
+        self.write_line(writer, spec)
+    }
+
+    /// Writes a slice of records in binary mode. The default
+    /// implementation writes the records one by one.
+    fn write_chunks<R: Write + Seek>(
+        records: &[Self],
+        writer: &mut R,
+        spec: &Schema,
+    ) -> Result<()> {
+        records
+            .iter()
+            .try_for_each(|record| record.write_chunk(writer, spec))
+    }
+}
+
+/// [PcdColumns](crate::record::PcdColumns) stores records in one `Vec`
+/// per field.
+///
+/// The trait is derived with `#[derive(PcdColumns)]` on the record type,
+/// which generates a `{Name}Columns` type.
+/// [Reader::read_columns()](crate::reader::Reader::read_columns) and
+/// [Writer::push_columns()](crate::writer::Writer::push_columns) decode
+/// and encode the columns without building records.
+pub trait PcdColumns: Default {
+    type Record;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reads a binary record and appends its values to the columns.
+    fn read_chunk<R: BufRead>(&mut self, reader: &mut R, field_defs: &Schema) -> Result<()>;
+
+    /// Reads an Ascii record and appends its values to the columns.
+    fn read_line<R: BufRead>(&mut self, reader: &mut R, field_defs: &Schema) -> Result<()>;
+
+    /// Writes the record at `index` in binary mode.
+    fn write_chunk<W: Write>(&self, index: usize, writer: &mut W, spec: &Schema) -> Result<()>;
+
+    /// Writes the record at `index` in Ascii mode.
+    fn write_line<W: Write>(
+        &self,
+        index: usize,
+        writer: &mut W,
+        spec: &Schema,
+        formats: &[FloatFormat],
+    ) -> Result<()>;
+}
+
+/// [PcdSchema](crate::record::PcdSchema) describes the fields of a
+/// record type as constants, so that no schema has to be built to size
+/// buffers or compare layouts.
+///
+/// The trait is derived with `#[derive(PcdSchema)]`. Ignored fields keep
+/// their Rust names, and `Vec` fields need the `#[pcd(count = N)]`
+/// attribute.
+pub trait PcdSchema {
+    /// The fields in order, without padding.
+    const FIELDS: &'static [StaticFieldDef];
+
+    /// The size of a binary record in bytes.
+    const RECORD_SIZE: usize = record_size(Self::FIELDS);
+
+    /// Builds the [Schema] of the fields.
+    fn schema() -> Schema {
+        Self::FIELDS
+            .iter()
+            .map(|def| (def.name, def.kind, def.count))
+            .collect()
+    }
+
+    /// Checks if the non-padding fields of the schema have the same
+    /// names, kinds and counts.
+    fn matches_schema(schema: &Schema) -> bool {
+        matches_static_fields(Self::FIELDS, schema)
+    }
+}
+
+/// Checks if the non-padding fields of the schema have the same names,
+/// kinds and counts as the fields.
+#[doc(hidden)]
+pub fn matches_static_fields(fields: &[StaticFieldDef], schema: &Schema) -> bool {
+    let mut defs = schema.iter().filter(|def| !def.padding);
+    let is_prefix_eq = fields.iter().all(|expect| {
+        defs.next().is_some_and(|def| {
+            def.name == expect.name && def.kind == expect.kind && def.count == expect.count
+        })
+    });
+    is_prefix_eq && defs.next().is_none()
+}
+
+/// A field in [PcdSchema::FIELDS].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaticFieldDef {
+    pub name: &'static str,
+    pub kind: ValueKind,
+    pub count: u64,
+}
+
+impl StaticFieldDef {
+    /// Gets the size of the field in bytes.
+    pub const fn size(&self) -> usize {
+        self.kind.size() * self.count as usize
+    }
+}
+
+const fn record_size(fields: &[StaticFieldDef]) -> usize {
+    let mut size = 0;
+    let mut index = 0;
+    while index < fields.len() {
+        size += fields[index].size();
+        index += 1;
+    }
+    size
 }
 
 // Runtime record types
@@ -143,6 +525,66 @@ impl Field {
         }
     }
 
+    /// Creates a field of `count` zeros of the value kind.
+    pub fn zeros(kind: ValueKind, count: usize) -> Field {
+        use Field as F;
+        use ValueKind as K;
+
+        match kind {
+            K::I8 => F::I8(vec![0; count]),
+            K::I16 => F::I16(vec![0; count]),
+            K::I32 => F::I32(vec![0; count]),
+            K::U8 => F::U8(vec![0; count]),
+            K::U16 => F::U16(vec![0; count]),
+            K::U32 => F::U32(vec![0; count]),
+            K::F32 => F::F32(vec![0.0; count]),
+            K::F64 => F::F64(vec![0.0; count]),
+        }
+    }
+
+    /// Converts the values to another value kind.
+    ///
+    /// It returns `None` if any value cannot be represented in the
+    /// target kind.
+    pub fn cast(&self, kind: ValueKind) -> Option<Field> {
+        use Field as F;
+        use ValueKind as K;
+
+        fn cast_values<S, T>(values: &[S]) -> Option<Vec<T>>
+        where
+            S: NumCast + Copy,
+            T: NumCast,
+        {
+            values.iter().map(|&value| T::from(value)).collect()
+        }
+
+        macro_rules! cast_to {
+            ($values:expr) => {
+                match kind {
+                    K::I8 => F::I8(cast_values($values)?),
+                    K::I16 => F::I16(cast_values($values)?),
+                    K::I32 => F::I32(cast_values($values)?),
+                    K::U8 => F::U8(cast_values($values)?),
+                    K::U16 => F::U16(cast_values($values)?),
+                    K::U32 => F::U32(cast_values($values)?),
+                    K::F32 => F::F32(cast_values($values)?),
+                    K::F64 => F::F64(cast_values($values)?),
+                }
+            };
+        }
+
+        Some(match self {
+            F::I8(values) => cast_to!(values),
+            F::I16(values) => cast_to!(values),
+            F::I32(values) => cast_to!(values),
+            F::U8(values) => cast_to!(values),
+            F::U16(values) => cast_to!(values),
+            F::U32(values) => cast_to!(values),
+            F::F32(values) => cast_to!(values),
+            F::F64(values) => cast_to!(values),
+        })
+    }
+
     pub fn to_value<T>(&self) -> Option<T>
     where
         T: Value + NumCast,
@@ -273,6 +715,111 @@ impl DynRecord {
     }
 }
 
+/// The fields of a [DynRecord] looked up by name, which is used by
+/// the derived conversions from [DynRecord].
+#[doc(hidden)]
+pub struct DynRecordFields<'a> {
+    record: &'a DynRecord,
+    schema: Schema,
+}
+
+impl<'a> DynRecordFields<'a> {
+    /// Creates the lookup, failing if the record does not match the
+    /// schema without padding fields.
+    pub fn new(record: &'a DynRecord, schema: &Schema) -> Result<Self> {
+        let schema = schema.without_padding();
+        if !record.is_schema_consistent(&schema) {
+            return Err(
+                Error::new_invalid_argument_error("record does not match the schema").into(),
+            );
+        }
+        Ok(Self { record, schema })
+    }
+
+    /// Finds the field of an entry in [PcdDeserialize::read_spec].
+    ///
+    /// It returns `None` if the field is missing, and fails with
+    /// [FieldKindMismatchError](Error::FieldKindMismatchError) or
+    /// [FieldSizeMismatchError](Error::FieldSizeMismatchError) if the
+    /// field has another kind or count. Ignored fields are zeros.
+    pub fn find(
+        &self,
+        spec: &'a (Option<String>, ValueKind, Option<usize>),
+    ) -> Result<Option<FieldValues<'a>>> {
+        let (name_opt, kind, count_opt) = spec;
+        let Some(name) = name_opt else {
+            let field = Field::zeros(*kind, count_opt.unwrap_or(0));
+            return Ok(Some(FieldValues::new("_", Cow::Owned(field))));
+        };
+        let Some(index) = self.schema.index_of(name) else {
+            return Ok(None);
+        };
+
+        let field = &self.record.0[index];
+        if field.kind() != *kind {
+            return Err(Error::new_field_kind_mismatch_error(name, *kind, field.kind()).into());
+        }
+        if let Some(count) = *count_opt {
+            if field.count() != count {
+                return Err(
+                    Error::new_field_size_mismatch_error(name, count, field.count()).into(),
+                );
+            }
+        }
+
+        Ok(Some(FieldValues::new(name, Cow::Borrowed(field))))
+    }
+
+    /// Gets the field like [find](Self::find), failing with
+    /// [FieldNotFoundError](Error::FieldNotFoundError) if it is missing.
+    pub fn get(
+        &self,
+        spec: &'a (Option<String>, ValueKind, Option<usize>),
+    ) -> Result<FieldValues<'a>> {
+        let name = spec.0.as_deref().unwrap_or("_");
+        self.find(spec)?
+            .ok_or_else(|| Error::new_field_not_found_error(name).into())
+    }
+}
+
+/// Reads the values of a [Field] in order.
+#[doc(hidden)]
+pub struct FieldValues<'a> {
+    name: &'a str,
+    field: Cow<'a, Field>,
+    index: usize,
+}
+
+impl<'a> FieldValues<'a> {
+    fn new(name: &'a str, field: Cow<'a, Field>) -> Self {
+        Self {
+            name,
+            field,
+            index: 0,
+        }
+    }
+
+    /// Gets the number of values in the field.
+    pub fn count(&self) -> usize {
+        self.field.count()
+    }
+
+    /// Reads the next value, which must be of the field kind.
+    pub fn next_value<T: StoredValue>(&mut self) -> Result<T> {
+        let field = &*self.field;
+        if field.kind() != T::KIND {
+            let error = Error::new_field_kind_mismatch_error(self.name, T::KIND, field.kind());
+            return Err(error.into());
+        }
+
+        let value = T::from_field(field, self.index).ok_or_else(|| {
+            Error::new_field_size_mismatch_error(self.name, self.index + 1, field.count())
+        })?;
+        self.index += 1;
+        Ok(value)
+    }
+}
+
 impl PcdSerialize for DynRecord {
     fn is_dynamic() -> bool {
         true
@@ -349,6 +896,18 @@ impl PcdSerialize for DynRecord {
     }
 
     fn write_line<Writer>(&self, writer: &mut Writer, spec: &Schema) -> Result<()>
+    where
+        Writer: Write + Seek,
+    {
+        self.write_line_with(writer, spec, &[])
+    }
+
+    fn write_line_with<Writer>(
+        &self,
+        writer: &mut Writer,
+        spec: &Schema,
+        formats: &[FloatFormat],
+    ) -> Result<()>
     where
         Writer: Write + Seek,
     {
@@ -358,9 +917,11 @@ impl PcdSerialize for DynRecord {
 
         let mut tokens = vec![];
 
-        for field in self.0.iter() {
+        for (index, field) in self.0.iter().enumerate() {
             use Field as F;
 
+            let format = formats.get(index).copied().unwrap_or_default();
+
             match field {
                 F::I8(values) => {
                     let iter = values.iter().map(|val| val.to_string());
@@ -387,11 +948,11 @@ impl PcdSerialize for DynRecord {
                     tokens.extend(iter);
                 }
                 F::F32(values) => {
-                    let iter = values.iter().map(|val| val.to_string());
+                    let iter = values.iter().map(|val| format.format(*val));
                     tokens.extend(iter);
                 }
                 F::F64(values) => {
-                    let iter = values.iter().map(|val| val.to_string());
+                    let iter = values.iter().map(|val| format.format(*val));
                     tokens.extend(iter);
                 }
             }
@@ -481,87 +1042,92 @@ impl PcdDeserialize for DynRecord {
     }
 
     fn read_line<R: BufRead>(reader: &mut R, field_defs: &Schema) -> Result<Self> {
-        let mut line = String::new();
-        reader.read_line(&mut line)?;
-        let tokens = line.split_ascii_whitespace().collect::<Vec<_>>();
-
-        {
-            let expect = field_defs.iter().map(|def| def.count as usize).sum();
-            let error = Error::new_text_token_mismatch_error(expect, tokens.len());
-            if tokens.len() != expect {
-                return Err(error.into());
+        scan_line(reader, |mut tokens| {
+            {
+                let expect = field_defs.iter().map(|def| def.count as usize).sum();
+                let found = tokens.clone().count();
+                if found != expect {
+                    return Err(Error::new_text_token_mismatch_error(expect, found).into());
+                }
             }
-        }
 
-        let mut tokens_iter = tokens.into_iter();
-        let fields = field_defs
-            .iter()
-            .map(|def| {
-                let FieldDef { kind, count, .. } = *def;
-
-                let counter = 0..count;
-
-                let field = match kind {
-                    ValueKind::I8 => {
-                        let values = counter
-                            .map(|_| Ok(tokens_iter.next().unwrap().parse()?))
-                            .collect::<Result<Vec<_>>>()?;
-                        Field::I8(values)
-                    }
-                    ValueKind::I16 => {
-                        let values = counter
-                            .map(|_| Ok(tokens_iter.next().unwrap().parse()?))
-                            .collect::<Result<Vec<_>>>()?;
-                        Field::I16(values)
-                    }
-                    ValueKind::I32 => {
-                        let values = counter
-                            .map(|_| Ok(tokens_iter.next().unwrap().parse()?))
-                            .collect::<Result<Vec<_>>>()?;
-                        Field::I32(values)
-                    }
-                    ValueKind::U8 => {
-                        let values = counter
-                            .map(|_| Ok(tokens_iter.next().unwrap().parse()?))
-                            .collect::<Result<Vec<_>>>()?;
-                        Field::U8(values)
-                    }
-                    ValueKind::U16 => {
-                        let values = counter
-                            .map(|_| Ok(tokens_iter.next().unwrap().parse()?))
-                            .collect::<Result<Vec<_>>>()?;
-                        Field::U16(values)
-                    }
-                    ValueKind::U32 => {
-                        let values = counter
-                            .map(|_| Ok(tokens_iter.next().unwrap().parse()?))
-                            .collect::<Result<Vec<_>>>()?;
-                        Field::U32(values)
-                    }
-                    ValueKind::F32 => {
-                        let values = counter
-                            .map(|_| Ok(tokens_iter.next().unwrap().parse()?))
-                            .collect::<Result<Vec<_>>>()?;
-                        Field::F32(values)
-                    }
-                    ValueKind::F64 => {
-                        let values = counter
-                            .map(|_| Ok(tokens_iter.next().unwrap().parse()?))
-                            .collect::<Result<Vec<_>>>()?;
-                        Field::F64(values)
-                    }
-                };
-
-                Ok(field)
-            })
-            .collect::<Result<Vec<_>>>()?;
-
-        Ok(Self(fields))
+            let fields = field_defs
+                .iter()
+                .map(|def| {
+                    let FieldDef { kind, count, .. } = *def;
+
+                    let counter = 0..count;
+
+                    let field = match kind {
+                        ValueKind::I8 => {
+                            let values = counter
+                                .map(|_| tokens.next_value())
+                                .collect::<Result<Vec<_>>>()?;
+                            Field::I8(values)
+                        }
+                        ValueKind::I16 => {
+                            let values = counter
+                                .map(|_| tokens.next_value())
+                                .collect::<Result<Vec<_>>>()?;
+                            Field::I16(values)
+                        }
+                        ValueKind::I32 => {
+                            let values = counter
+                                .map(|_| tokens.next_value())
+                                .collect::<Result<Vec<_>>>()?;
+                            Field::I32(values)
+                        }
+                        ValueKind::U8 => {
+                            let values = counter
+                                .map(|_| tokens.next_value())
+                                .collect::<Result<Vec<_>>>()?;
+                            Field::U8(values)
+                        }
+                        ValueKind::U16 => {
+                            let values = counter
+                                .map(|_| tokens.next_value())
+                                .collect::<Result<Vec<_>>>()?;
+                            Field::U16(values)
+                        }
+                        ValueKind::U32 => {
+                            let values = counter
+                                .map(|_| tokens.next_value())
+                                .collect::<Result<Vec<_>>>()?;
+                            Field::U32(values)
+                        }
+                        ValueKind::F32 => {
+                            let values = counter
+                                .map(|_| tokens.next_value())
+                                .collect::<Result<Vec<_>>>()?;
+                            Field::F32(values)
+                        }
+                        ValueKind::F64 => {
+                            let values = counter
+                                .map(|_| tokens.next_value())
+                                .collect::<Result<Vec<_>>>()?;
+                            Field::F64(values)
+                        }
+                    };
+
+                    Ok(field)
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            Ok(Self(fields))
+        })
     }
 }
 
 // impl for primitive types
 
+fn read_single_value<T: FromToken>(mut tokens: Tokens<'_>) -> Result<T> {
+    let found = tokens.clone().count();
+    if found != 1 {
+        return Err(Error::new_text_token_mismatch_error(1, found).into());
+    }
+    tokens.next_value()
+}
+
 impl PcdDeserialize for u8 {
     fn is_dynamic() -> bool {
         false
@@ -577,9 +1143,7 @@ impl PcdDeserialize for u8 {
     }
 
     fn read_line<R: BufRead>(reader: &mut R, _field_defs: &Schema) -> Result<Self> {
-        let mut line = String::new();
-        reader.read_line(&mut line)?;
-        Ok(line.parse()?)
+        scan_line(reader, read_single_value)
     }
 }
 
@@ -598,9 +1162,7 @@ impl PcdDeserialize for i8 {
     }
 
     fn read_line<R: BufRead>(reader: &mut R, _field_defs: &Schema) -> Result<Self> {
-        let mut line = String::new();
-        reader.read_line(&mut line)?;
-        Ok(line.parse()?)
+        scan_line(reader, read_single_value)
     }
 }
 
@@ -621,9 +1183,7 @@ macro_rules! impl_primitive {
             }
 
             fn read_line<R: BufRead>(reader: &mut R, _field_defs: &Schema) -> Result<Self> {
-                let mut line = String::new();
-                reader.read_line(&mut line)?;
-                Ok(line.parse()?)
+                scan_line(reader, read_single_value)
             }
         }
     };
@@ -635,3 +1195,177 @@ impl_primitive!(i16, I16, read_i16);
 impl_primitive!(i32, I32, read_i32);
 impl_primitive!(f32, F32, read_f32);
 impl_primitive!(f64, F64, read_f64);
+
+/// Gets the default name of the field at `index` of tuples and arrays,
+/// which is `x`, `y`, `z`, and then `field_N`.
+fn positional_name(index: usize) -> String {
+    match index {
+        0 => "x".to_string(),
+        1 => "y".to_string(),
+        2 => "z".to_string(),
+        _ => format!("field_{}", index),
+    }
+}
+
+fn check_token_count(tokens: &Tokens<'_>, expect: usize) -> Result<()> {
+    let found = tokens.clone().count();
+    if found != expect {
+        return Err(Error::new_text_token_mismatch_error(expect, found).into());
+    }
+    Ok(())
+}
+
+// Tuples and arrays are records with one field for each element
+
+macro_rules! impl_tuple_record {
+    ($len:expr; $(($index:tt, $ty:ident)),+) => {
+        impl<$($ty: StoredValue),+> PcdDeserialize for ($($ty,)+) {
+            fn is_dynamic() -> bool {
+                false
+            }
+
+            fn read_spec() -> Vec<(Option<String>, ValueKind, Option<usize>)> {
+                vec![$((None, <$ty as StoredValue>::KIND, Some(1))),+]
+            }
+
+            fn read_chunk<R: BufRead>(reader: &mut R, _field_defs: &Schema) -> Result<Self> {
+                Ok(($(<$ty as StoredValue>::read_value(reader)?,)+))
+            }
+
+            fn read_line<R: BufRead>(reader: &mut R, _field_defs: &Schema) -> Result<Self> {
+                scan_line(reader, |mut tokens| {
+                    check_token_count(&tokens, $len)?;
+                    Ok(($(<$ty as StoredValue>::read_token(&mut tokens)?,)+))
+                })
+            }
+        }
+
+        impl<$($ty: StoredValue),+> PcdSerialize for ($($ty,)+) {
+            fn is_dynamic() -> bool {
+                false
+            }
+
+            fn write_spec() -> Schema {
+                [$((positional_name($index), <$ty as StoredValue>::KIND, 1)),+]
+                    .into_iter()
+                    .collect()
+            }
+
+            fn is_positional() -> bool {
+                true
+            }
+
+            fn write_chunk<W: Write + Seek>(&self, writer: &mut W, _spec: &Schema) -> Result<()> {
+                $(self.$index.write_value(writer)?;)+
+                Ok(())
+            }
+
+            fn write_line<W: Write + Seek>(&self, writer: &mut W, spec: &Schema) -> Result<()> {
+                self.write_line_with(writer, spec, &[])
+            }
+
+            fn write_line_with<W: Write + Seek>(
+                &self,
+                writer: &mut W,
+                _spec: &Schema,
+                formats: &[FloatFormat],
+            ) -> Result<()> {
+                let tokens = [$(
+                    self.$index.to_token(formats.get($index).copied().unwrap_or_default())
+                ),+];
+                writeln!(writer, "{}", tokens.join(" "))?;
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_tuple_record!(1; (0, T0));
+impl_tuple_record!(2; (0, T0), (1, T1));
+impl_tuple_record!(3; (0, T0), (1, T1), (2, T2));
+impl_tuple_record!(4; (0, T0), (1, T1), (2, T2), (3, T3));
+impl_tuple_record!(5; (0, T0), (1, T1), (2, T2), (3, T3), (4, T4));
+impl_tuple_record!(6; (0, T0), (1, T1), (2, T2), (3, T3), (4, T4), (5, T5));
+impl_tuple_record!(7; (0, T0), (1, T1), (2, T2), (3, T3), (4, T4), (5, T5), (6, T6));
+impl_tuple_record!(8; (0, T0), (1, T1), (2, T2), (3, T3), (4, T4), (5, T5), (6, T6), (7, T7));
+impl_tuple_record!(
+    9; (0, T0), (1, T1), (2, T2), (3, T3), (4, T4), (5, T5), (6, T6), (7, T7), (8, T8)
+);
+impl_tuple_record!(
+    10; (0, T0), (1, T1), (2, T2), (3, T3), (4, T4), (5, T5), (6, T6), (7, T7), (8, T8),
+    (9, T9)
+);
+impl_tuple_record!(
+    11; (0, T0), (1, T1), (2, T2), (3, T3), (4, T4), (5, T5), (6, T6), (7, T7), (8, T8),
+    (9, T9), (10, T10)
+);
+impl_tuple_record!(
+    12; (0, T0), (1, T1), (2, T2), (3, T3), (4, T4), (5, T5), (6, T6), (7, T7), (8, T8),
+    (9, T9), (10, T10), (11, T11)
+);
+
+impl<T: StoredValue, const N: usize> PcdDeserialize for [T; N] {
+    fn is_dynamic() -> bool {
+        false
+    }
+
+    fn read_spec() -> Vec<(Option<String>, ValueKind, Option<usize>)> {
+        vec![(None, T::KIND, Some(1)); N]
+    }
+
+    fn read_chunk<R: BufRead>(reader: &mut R, _field_defs: &Schema) -> Result<Self> {
+        let values: Vec<T> = (0..N)
+            .map(|_| T::read_value(reader))
+            .collect::<Result<_>>()?;
+        Ok(values.try_into().unwrap_or_else(|_| unreachable!()))
+    }
+
+    fn read_line<R: BufRead>(reader: &mut R, _field_defs: &Schema) -> Result<Self> {
+        scan_line(reader, |mut tokens| {
+            check_token_count(&tokens, N)?;
+            let values: Vec<T> = (0..N)
+                .map(|_| T::read_token(&mut tokens))
+                .collect::<Result<_>>()?;
+            Ok(values.try_into().unwrap_or_else(|_| unreachable!()))
+        })
+    }
+}
+
+impl<T: StoredValue, const N: usize> PcdSerialize for [T; N] {
+    fn is_dynamic() -> bool {
+        false
+    }
+
+    fn write_spec() -> Schema {
+        (0..N)
+            .map(|index| (positional_name(index), T::KIND, 1))
+            .collect()
+    }
+
+    fn is_positional() -> bool {
+        true
+    }
+
+    fn write_chunk<W: Write + Seek>(&self, writer: &mut W, _spec: &Schema) -> Result<()> {
+        self.iter().try_for_each(|value| value.write_value(writer))
+    }
+
+    fn write_line<W: Write + Seek>(&self, writer: &mut W, spec: &Schema) -> Result<()> {
+        self.write_line_with(writer, spec, &[])
+    }
+
+    fn write_line_with<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        _spec: &Schema,
+        formats: &[FloatFormat],
+    ) -> Result<()> {
+        let tokens: Vec<_> = self
+            .iter()
+            .enumerate()
+            .map(|(index, value)| value.to_token(formats.get(index).copied().unwrap_or_default()))
+            .collect();
+        writeln!(writer, "{}", tokens.join(" "))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,273 @@
+//! Conversions between PCD data and ROS `sensor_msgs/PointCloud2` messages.
+//!
+//! The [PointCloud2] and [PointField] types mirror the ROS message
+//! definitions without depending on any ROS client library. The
+//! binary record data is converted from and to the little-endian
+//! layout used by the `DATA binary` section of PCD files, where gaps
+//! between point fields are represented by padding fields.
+//!
+//! ```rust
+//! # use anyhow::Result;
+//! # fn main() -> Result<()> {
+//! use pcd_rs::{ros::PointCloud2, DataKind, PcdMeta, Schema, ValueKind, ViewPoint};
+//!
+//! let meta = PcdMeta {
+//!     version: "0.7".into(),
+//!     comments: vec![],
+//!     width: 2,
+//!     height: 1,
+//!     viewpoint: ViewPoint::default(),
+//!     num_points: 2,
+//!     data: DataKind::Binary,
+//!     field_defs: Schema::from_iter([("x", ValueKind::F32, 1), ("label", ValueKind::U8, 1)]),
+//! };
+//! let data: Vec<u8> = [1.0f32.to_le_bytes().as_slice(), &[3], &2.0f32.to_le_bytes(), &[4]].concat();
+//!
+//! let msg = PointCloud2::from_pcd(&meta, &data)?;
+//! assert_eq!(msg.point_step, 5);
+//!
+//! let (new_meta, new_data) = msg.to_pcd()?;
+//! assert_eq!(new_meta.field_defs, meta.field_defs);
+//! assert_eq!(new_data, data);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::Error,
+    metas::{DataKind, PcdMeta, Schema, ValueKind, ViewPoint},
+    utils,
+};
+use anyhow::Result;
+
+/// Represents a `sensor_msgs/PointField` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PointField {
+    pub name: String,
+    pub offset: u32,
+    pub datatype: u8,
+    pub count: u32,
+}
+
+impl PointField {
+    pub const INT8: u8 = 1;
+    pub const UINT8: u8 = 2;
+    pub const INT16: u8 = 3;
+    pub const UINT16: u8 = 4;
+    pub const INT32: u8 = 5;
+    pub const UINT32: u8 = 6;
+    pub const FLOAT32: u8 = 7;
+    pub const FLOAT64: u8 = 8;
+
+    /// Gets the PCD value kind of the field datatype, or `None` if the
+    /// datatype is unknown.
+    pub fn kind(&self) -> Option<ValueKind> {
+        use ValueKind as K;
+
+        let kind = match self.datatype {
+            Self::INT8 => K::I8,
+            Self::UINT8 => K::U8,
+            Self::INT16 => K::I16,
+            Self::UINT16 => K::U16,
+            Self::INT32 => K::I32,
+            Self::UINT32 => K::U32,
+            Self::FLOAT32 => K::F32,
+            Self::FLOAT64 => K::F64,
+            _ => return None,
+        };
+        Some(kind)
+    }
+}
+
+/// Represents a `sensor_msgs/PointCloud2` message, leaving out the header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PointCloud2 {
+    pub height: u32,
+    pub width: u32,
+    pub fields: Vec<PointField>,
+    pub is_bigendian: bool,
+    pub point_step: u32,
+    pub row_step: u32,
+    pub data: Vec<u8>,
+    pub is_dense: bool,
+}
+
+impl PointCloud2 {
+    /// Builds a message from PCD meta data and binary record data.
+    ///
+    /// The `data` must contain `meta.num_points` little-endian records
+    /// laid out by `meta.field_defs`. Padding fields become gaps
+    /// between point fields. The `is_dense` is set if the x, y and z
+    /// values of all points are finite.
+    pub fn from_pcd(meta: &PcdMeta, data: &[u8]) -> Result<Self> {
+        if meta.width.checked_mul(meta.height) != Some(meta.num_points) {
+            return Err(Error::new_invalid_argument_error(
+                "width and height do not agree with the number of points",
+            )
+            .into());
+        }
+
+        let fields: Vec<_> = meta
+            .field_defs
+            .iter()
+            .filter(|def| !def.padding)
+            .map(|def| {
+                Ok(PointField {
+                    name: def.name.clone(),
+                    offset: to_u32(def.offset, "field offset")?,
+                    datatype: datatype_of(def.kind),
+                    count: to_u32(def.count, "field count")?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        let point_step = meta.field_defs.record_size_bytes();
+
+        let expect = usize::try_from(meta.num_points)
+            .ok()
+            .and_then(|num_points| point_step.checked_mul(num_points))
+            .ok_or_else(|| Error::new_invalid_argument_error("record data size overflows"))?;
+        if data.len() != expect {
+            let desc = format!(
+                "expect {} bytes of record data, but found {} bytes",
+                expect,
+                data.len()
+            );
+            return Err(Error::new_invalid_argument_error(&desc).into());
+        }
+
+        let xyz_fields = utils::find_xyz_fields(&meta.field_defs);
+        let is_dense = point_step == 0
+            || data
+                .chunks_exact(point_step)
+                .all(|chunk| utils::is_chunk_finite(chunk, &meta.field_defs, &xyz_fields));
+
+        let point_step = to_u32(point_step as u64, "point step")?;
+        let width = to_u32(meta.width, "width")?;
+        let row_step = point_step
+            .checked_mul(width)
+            .ok_or_else(|| Error::new_invalid_argument_error("row step overflows u32"))?;
+
+        Ok(Self {
+            height: to_u32(meta.height, "height")?,
+            width,
+            fields,
+            is_bigendian: false,
+            point_step,
+            row_step,
+            data: data.to_vec(),
+            is_dense,
+        })
+    }
+
+    /// Converts the message to PCD meta data and binary record data.
+    ///
+    /// Fields are ordered by their offsets. The gaps between fields
+    /// and the trailing bytes up to `point_step` become padding
+    /// fields, whose bytes are copied as is. Big-endian messages are
+    /// converted to little-endian.
+    pub fn to_pcd(&self) -> Result<(PcdMeta, Vec<u8>)> {
+        let point_step = self.point_step as usize;
+        let row_step = self.row_step as usize;
+        let width = self.width as usize;
+        let height = self.height as usize;
+
+        let fields: Vec<_> = self
+            .fields
+            .iter()
+            .map(|field| {
+                let kind = field.kind().ok_or_else(|| {
+                    let desc = format!(
+                        "field {:?} has unknown datatype {}",
+                        field.name, field.datatype
+                    );
+                    Error::new_invalid_argument_error(&desc)
+                })?;
+                Ok((
+                    field.name.as_str(),
+                    kind,
+                    field.count as u64,
+                    field.offset as u64,
+                ))
+            })
+            .collect::<Result<_>>()?;
+        let field_defs = Schema::from_offsets(fields, point_step as u64)?;
+
+        let overflow = || Error::new_invalid_argument_error("data size overflows");
+        let row_size = point_step.checked_mul(width).ok_or_else(overflow)?;
+        if row_size > row_step {
+            return Err(Error::new_invalid_argument_error(
+                "point step and width exceed the row step",
+            )
+            .into());
+        }
+        if height > 0 {
+            let expect = row_step
+                .checked_mul(height - 1)
+                .and_then(|size| size.checked_add(row_size))
+                .ok_or_else(overflow)?;
+            if self.data.len() < expect {
+                return Err(
+                    Error::new_truncated_data_error(expect as u64, self.data.len() as u64).into(),
+                );
+            }
+        }
+        let num_points = width.checked_mul(height).ok_or_else(overflow)?;
+
+        let mut data = Vec::with_capacity(row_size * height);
+
+        for row in 0..height {
+            for col in 0..width {
+                let begin = row * row_step + col * point_step;
+                let point = &self.data[begin..(begin + point_step)];
+                let record_begin = data.len();
+                data.extend_from_slice(point);
+
+                if self.is_bigendian {
+                    let record = &mut data[record_begin..];
+                    for def in field_defs.iter().filter(|def| !def.padding) {
+                        let begin = def.offset as usize;
+                        let end = begin + def.size();
+                        for value in record[begin..end].chunks_exact_mut(def.kind.size()) {
+                            value.reverse();
+                        }
+                    }
+                }
+            }
+        }
+
+        let meta = PcdMeta {
+            version: "0.7".to_string(),
+            comments: vec![],
+            width: self.width as u64,
+            height: self.height as u64,
+            viewpoint: ViewPoint::default(),
+            num_points: num_points as u64,
+            data: DataKind::Binary,
+            field_defs,
+        };
+
+        Ok((meta, data))
+    }
+}
+
+fn to_u32(value: u64, what: &str) -> Result<u32> {
+    u32::try_from(value).map_err(|_| {
+        let desc = format!("{} {} does not fit in u32", what, value);
+        Error::new_invalid_argument_error(&desc).into()
+    })
+}
+
+fn datatype_of(kind: ValueKind) -> u8 {
+    use ValueKind as K;
+
+    match kind {
+        K::I8 => PointField::INT8,
+        K::U8 => PointField::UINT8,
+        K::I16 => PointField::INT16,
+        K::U16 => PointField::UINT16,
+        K::I32 => PointField::INT32,
+        K::U32 => PointField::UINT32,
+        K::F32 => PointField::FLOAT32,
+        K::F64 => PointField::FLOAT64,
+    }
+}
@@ -0,0 +1,244 @@
+//! Zero-allocation scanning of Ascii point data.
+//!
+//! The [scan_line()] function borrows a line from the buffer of a
+//! [BufRead] and passes its [Tokens] to a closure. The line is copied
+//! only if it does not fit in the buffer. Tokens are parsed to values
+//! by [Tokens::next_value()] without UTF-8 validation for common
+//! number formats.
+//!
+//! ```rust
+//! # use anyhow::Result;
+//! # fn main() -> Result<()> {
+//! use pcd_rs::text::scan_line;
+//!
+//! let mut data = "1.5 -2 7\n".as_bytes();
+//! let (x, y, z) = scan_line(&mut data, |mut tokens| {
+//!     Ok((
+//!         tokens.next_value::<f32>()?,
+//!         tokens.next_value::<i16>()?,
+//!         tokens.next_value::<u8>()?,
+//!     ))
+//! })?;
+//! assert_eq!((x, y, z), (1.5, -2, 7));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{error::Error, traits::Value};
+use anyhow::Result;
+use std::io::prelude::*;
+
+/// Reads a line from the reader and passes its tokens to `f`.
+///
+/// The line is borrowed from the reader buffer if the line break is
+/// found in the buffer. Otherwise, the line is read into a temporary
+/// vector.
+pub fn scan_line<R, T, F>(reader: &mut R, f: F) -> Result<T>
+where
+    R: BufRead + ?Sized,
+    F: FnOnce(Tokens<'_>) -> Result<T>,
+{
+    let buf = reader.fill_buf()?;
+
+    match buf.iter().position(|&byte| byte == b'\n') {
+        Some(end) => {
+            let result = f(Tokens::new(&buf[..end]));
+            reader.consume(end + 1);
+            result
+        }
+        None => {
+            let mut line = vec![];
+            reader.read_until(b'\n', &mut line)?;
+            f(Tokens::new(&line))
+        }
+    }
+}
+
+/// An iterator over whitespace separated tokens in a line.
+#[derive(Debug, Clone)]
+pub struct Tokens<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> Tokens<'a> {
+    pub fn new(line: &'a [u8]) -> Self {
+        Self { rest: line }
+    }
+
+    /// Parses the next token to a value.
+    ///
+    /// It fails with [TextTokenMismatchError](Error::TextTokenMismatchError)
+    /// if no tokens are left, or
+    /// [InvalidTokenError](Error::InvalidTokenError) if the token
+    /// cannot be parsed.
+    pub fn next_value<T>(&mut self) -> Result<T>
+    where
+        T: FromToken,
+    {
+        let token = self
+            .next()
+            .ok_or_else(|| Error::new_text_token_mismatch_error(1, 0))?;
+        T::from_token(token).ok_or_else(|| Error::new_invalid_token_error(token, T::KIND).into())
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let begin = self
+            .rest
+            .iter()
+            .position(|byte| !byte.is_ascii_whitespace())?;
+        let rest = &self.rest[begin..];
+        let end = rest
+            .iter()
+            .position(|byte| byte.is_ascii_whitespace())
+            .unwrap_or(rest.len());
+
+        let (token, rest) = rest.split_at(end);
+        self.rest = rest;
+        Some(token)
+    }
+}
+
+/// The trait parses a primitive value from an Ascii token.
+pub trait FromToken: Value {
+    fn from_token(token: &[u8]) -> Option<Self>;
+}
+
+macro_rules! impl_from_token_int {
+    ($ty:ty, $parse:ident) => {
+        impl FromToken for $ty {
+            fn from_token(token: &[u8]) -> Option<Self> {
+                $parse(token)?.try_into().ok()
+            }
+        }
+    };
+}
+
+impl_from_token_int!(u8, parse_unsigned);
+impl_from_token_int!(u16, parse_unsigned);
+impl_from_token_int!(u32, parse_unsigned);
+impl_from_token_int!(i8, parse_signed);
+impl_from_token_int!(i16, parse_signed);
+impl_from_token_int!(i32, parse_signed);
+
+macro_rules! impl_from_token_float {
+    ($ty:ty, $max_mantissa:expr, $pow10:expr) => {
+        impl FromToken for $ty {
+            fn from_token(token: &[u8]) -> Option<Self> {
+                const POW10: &[$ty] = &$pow10;
+
+                // Clinger's fast path is exact when both the mantissa and
+                // the power of ten are exactly representable.
+                if let Some((negative, mantissa, exp10)) = parse_decimal(token) {
+                    if mantissa <= $max_mantissa && exp10.unsigned_abs() < POW10.len() as u64 {
+                        let value = mantissa as $ty;
+                        let value = if exp10 >= 0 {
+                            value * POW10[exp10 as usize]
+                        } else {
+                            value / POW10[-exp10 as usize]
+                        };
+                        return Some(if negative { -value } else { value });
+                    }
+                }
+
+                std::str::from_utf8(token).ok()?.parse().ok()
+            }
+        }
+    };
+}
+
+impl_from_token_float!(
+    f32,
+    1 << 24,
+    [1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10]
+);
+impl_from_token_float!(
+    f64,
+    1 << 53,
+    [
+        1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15, 1e16,
+        1e17, 1e18, 1e19, 1e20, 1e21, 1e22,
+    ]
+);
+
+fn split_sign(token: &[u8]) -> (bool, &[u8]) {
+    match token.split_first() {
+        Some((b'-', rest)) => (true, rest),
+        Some((b'+', rest)) => (false, rest),
+        _ => (false, token),
+    }
+}
+
+fn parse_digits(digits: &[u8]) -> Option<u64> {
+    if digits.is_empty() {
+        return None;
+    }
+
+    digits.iter().try_fold(0u64, |value, &byte| {
+        let digit = (byte as char).to_digit(10)?;
+        value.checked_mul(10)?.checked_add(digit as u64)
+    })
+}
+
+fn parse_unsigned(token: &[u8]) -> Option<u64> {
+    let digits = token.strip_prefix(b"+").unwrap_or(token);
+    parse_digits(digits)
+}
+
+fn parse_signed(token: &[u8]) -> Option<i64> {
+    let (negative, digits) = split_sign(token);
+    let value = parse_digits(digits)? as i128;
+    let value = if negative { -value } else { value };
+    value.try_into().ok()
+}
+
+/// Parses a decimal number in the form of `[+-]digits[.digits][e[+-]digits]`
+/// into the sign, the mantissa and the power of ten. It returns `None`
+/// for other forms or if the mantissa has more than 19 digits.
+fn parse_decimal(token: &[u8]) -> Option<(bool, u64, i64)> {
+    let (negative, rest) = split_sign(token);
+
+    let (number, exp) = match rest.iter().position(|&byte| byte == b'e' || byte == b'E') {
+        Some(pos) => (&rest[..pos], Some(&rest[(pos + 1)..])),
+        None => (rest, None),
+    };
+    let (int_digits, frac_digits) = match number.iter().position(|&byte| byte == b'.') {
+        Some(pos) => (&number[..pos], &number[(pos + 1)..]),
+        None => (number, &[][..]),
+    };
+
+    if int_digits.len() + frac_digits.len() > 19 {
+        return None;
+    }
+    let mantissa = match (int_digits.is_empty(), frac_digits.is_empty()) {
+        (true, true) => return None,
+        (false, true) => parse_digits(int_digits)?,
+        (true, false) => parse_digits(frac_digits)?,
+        (false, false) => {
+            let int_part = parse_digits(int_digits)?;
+            let frac_part = parse_digits(frac_digits)?;
+            int_part * 10u64.pow(frac_digits.len() as u32) + frac_part
+        }
+    };
+
+    let exp = match exp {
+        Some(exp) => {
+            let (exp_negative, exp_digits) = split_sign(exp);
+            if exp_digits.len() > 4 {
+                return None;
+            }
+            let exp = parse_digits(exp_digits)? as i64;
+            if exp_negative {
+                -exp
+            } else {
+                exp
+            }
+        }
+        None => 0,
+    };
+
+    Some((negative, mantissa, exp - frac_digits.len() as i64))
+}
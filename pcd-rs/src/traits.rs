@@ -1,6 +1,7 @@
 //! Traits definitions.
 
-use crate::ValueKind;
+use crate::{codec::StoredValue, error::Error, ValueKind};
+use anyhow::Result;
 
 /// This trait assocaites Rust primitive types to PCD primitive types.
 pub trait Value
@@ -41,3 +42,37 @@ impl Value for f32 {
 impl Value for f64 {
     const KIND: ValueKind = ValueKind::F64;
 }
+
+/// This trait converts field types to and from stored primitive values,
+/// so that derived records can have fields of the type.
+///
+/// It is implemented for `bool` stored as `U8`, and can be derived on
+/// fieldless enums with `#[repr(u8)]`, `#[repr(u16)]` or `#[repr(u32)]`.
+/// Decoding fails with
+/// [UnknownDiscriminantError](Error::UnknownDiscriminantError) for
+/// values that match no variant.
+pub trait PcdValue
+where
+    Self: Copy,
+{
+    type Repr: StoredValue;
+
+    fn to_repr(&self) -> Self::Repr;
+    fn from_repr(repr: Self::Repr) -> Result<Self>;
+}
+
+impl PcdValue for bool {
+    type Repr = u8;
+
+    fn to_repr(&self) -> u8 {
+        *self as u8
+    }
+
+    fn from_repr(repr: u8) -> Result<Self> {
+        match repr {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Error::new_unknown_discriminant_error("bool", repr as u64).into()),
+        }
+    }
+}
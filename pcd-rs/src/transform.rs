@@ -0,0 +1,213 @@
+//! Types for transforming the schema of untyped records while reading.
+//!
+//! A [SchemaMap] lists the output fields, where each output field is
+//! taken from a source field, optionally renamed or cast to another
+//! value kind. Source fields not listed are dropped. The
+//! [DynReader::map_schema()](crate::reader::Reader::map_schema) method
+//! applies the map to a [DynReader] and returns a [MappedReader].
+//!
+//! ```rust
+//! # use anyhow::Result;
+//! # fn main() -> Result<()> {
+//! use pcd_rs::{transform::SchemaMap, DynReader, ValueKind};
+//!
+//! let map = SchemaMap::new()
+//!     .field("z")
+//!     .cast("x", ValueKind::F64)
+//!     .rename("rgb", "color");
+//!
+//! let reader = DynReader::open("test_files/binary.pcd")?.map_schema(map)?;
+//! let names: Vec<_> = reader.meta().field_defs.iter().map(|def| &def.name).collect();
+//! assert_eq!(names, ["z", "x", "color"]);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::Error,
+    metas::{FieldDef, PcdMeta, Schema, ValueKind},
+    reader::{DynReader, Reader},
+    record::DynRecord,
+};
+use anyhow::Result;
+use std::io::prelude::*;
+
+/// Describes the output fields of a [MappedReader].
+#[derive(Debug, Clone, Default)]
+pub struct SchemaMap {
+    fields: Vec<FieldMap>,
+}
+
+#[derive(Debug, Clone)]
+struct FieldMap {
+    source: String,
+    name: String,
+    kind: Option<ValueKind>,
+}
+
+impl SchemaMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a map that selects the fields in the given order.
+    pub fn select<S>(names: &[S]) -> Self
+    where
+        S: AsRef<str>,
+    {
+        names
+            .iter()
+            .fold(Self::new(), |map, name| map.field(name.as_ref()))
+    }
+
+    /// Appends an output field copied from the source field.
+    pub fn field(self, source: impl Into<String>) -> Self {
+        let source = source.into();
+        self.push(source.clone(), source, None)
+    }
+
+    /// Appends an output field copied from the source field under a
+    /// new name.
+    pub fn rename(self, source: impl Into<String>, name: impl Into<String>) -> Self {
+        self.push(source.into(), name.into(), None)
+    }
+
+    /// Appends an output field converted from the source field to
+    /// another value kind.
+    pub fn cast(self, source: impl Into<String>, kind: ValueKind) -> Self {
+        let source = source.into();
+        self.push(source.clone(), source, Some(kind))
+    }
+
+    /// Appends an output field converted from the source field to
+    /// another value kind under a new name.
+    pub fn rename_cast(
+        self,
+        source: impl Into<String>,
+        name: impl Into<String>,
+        kind: ValueKind,
+    ) -> Self {
+        self.push(source.into(), name.into(), Some(kind))
+    }
+
+    fn push(mut self, source: String, name: String, kind: Option<ValueKind>) -> Self {
+        self.fields.push(FieldMap { source, name, kind });
+        self
+    }
+}
+
+/// The `MappedReader` loads untyped records and transforms them by a [SchemaMap].
+///
+/// The [meta()](MappedReader::meta) reflects the output schema, so
+/// that it can be used to configure a [WriterInit](crate::WriterInit).
+pub struct MappedReader<R>
+where
+    R: Read,
+{
+    reader: DynReader<R>,
+    meta: PcdMeta,
+    fields: Vec<(usize, Option<ValueKind>)>,
+}
+
+impl<R> MappedReader<R>
+where
+    R: BufRead,
+{
+    /// Get meta data with the output schema.
+    pub fn meta(&self) -> &PcdMeta {
+        &self.meta
+    }
+
+    fn map_record(&self, record: DynRecord) -> Result<DynRecord> {
+        let DynRecord(fields) = record;
+
+        let fields = self
+            .fields
+            .iter()
+            .zip(self.meta.field_defs.iter())
+            .map(|(&(index, kind), def)| {
+                let field = &fields[index];
+                match kind {
+                    Some(kind) => field
+                        .cast(kind)
+                        .ok_or_else(|| Error::new_field_cast_error(&def.name, kind).into()),
+                    None => Ok(field.clone()),
+                }
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(DynRecord(fields))
+    }
+}
+
+impl<R> Iterator for MappedReader<R>
+where
+    R: BufRead,
+{
+    type Item = Result<DynRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = self.reader.next()?;
+        Some(record.and_then(|record| self.map_record(record)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.reader.size_hint()
+    }
+}
+
+impl<R> Reader<DynRecord, R>
+where
+    R: BufRead,
+{
+    /// Keeps only the named fields in the given order.
+    pub fn project<S>(self, names: &[S]) -> Result<MappedReader<R>>
+    where
+        S: AsRef<str>,
+    {
+        self.map_schema(SchemaMap::select(names))
+    }
+
+    /// Transforms the records by a [SchemaMap].
+    ///
+    /// A source field can be used more than once, but the output
+    /// field names must be unique.
+    pub fn map_schema(self, map: SchemaMap) -> Result<MappedReader<R>> {
+        let record_spec = self.meta().field_defs.without_padding();
+
+        let (fields, defs): (Vec<_>, Vec<_>) = map
+            .fields
+            .into_iter()
+            .map(|FieldMap { source, name, kind }| {
+                let index = record_spec
+                    .index_of(&source)
+                    .ok_or_else(|| Error::new_field_not_found_error(&source))?;
+                let def = &record_spec[index];
+                let def = FieldDef {
+                    name,
+                    kind: kind.unwrap_or(def.kind),
+                    count: def.count,
+                    offset: 0,
+                    padding: false,
+                };
+                Ok(((index, kind), def))
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .unzip();
+
+        let field_defs: Schema = defs.into_iter().collect();
+        field_defs.validate()?;
+
+        let meta = PcdMeta {
+            field_defs,
+            ..self.meta().clone()
+        };
+
+        Ok(MappedReader {
+            reader: self,
+            meta,
+            fields,
+        })
+    }
+}
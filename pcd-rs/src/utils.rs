@@ -1,26 +1,45 @@
 use crate::{
     error::Error,
     metas::{DataKind, FieldDef, PcdMeta, Schema, TypeKind, ValueKind, ViewPoint},
+    text::{FromToken, Tokens},
+    writer::FloatFormat,
 };
 use anyhow::Result;
 use std::{collections::HashSet, io::prelude::*};
 
-pub fn load_meta<R: BufRead>(reader: &mut R, line_count: &mut usize) -> Result<PcdMeta> {
+/// Parses the PCD header, where `line_count` and `byte_count` are
+/// increased by the number of consumed lines and bytes.
+pub fn load_meta<R: BufRead>(
+    reader: &mut R,
+    line_count: &mut usize,
+    byte_count: &mut u64,
+) -> Result<PcdMeta> {
+    let mut comments = vec![];
+
     let mut get_meta_line = |expect_entry: &str| -> Result<_> {
         loop {
             let mut line = String::new();
             let read_size = reader.read_line(&mut line)?;
             *line_count += 1;
+            *byte_count += read_size as u64;
 
             if read_size == 0 {
                 return Err(Error::new_parse_error(*line_count, "Unexpected end of file").into());
             }
 
-            let line_stripped = match line.split('#').next() {
-                Some("") => continue,
-                Some(remaining) => remaining,
-                None => continue,
+            let (line_stripped, comment) = match line.split_once('#') {
+                Some((remaining, comment)) => {
+                    let comment = comment.strip_suffix('\n').unwrap_or(comment);
+                    let comment = comment.strip_suffix('\r').unwrap_or(comment);
+                    (remaining, Some(comment))
+                }
+                None => (line.as_str(), None),
             };
+            comments.extend(comment.map(|comment| comment.to_string()));
+
+            if line_stripped.is_empty() {
+                continue;
+            }
 
             let tokens: Vec<String> = line_stripped
                 .split_ascii_whitespace()
@@ -49,9 +68,10 @@ pub fn load_meta<R: BufRead>(reader: &mut R, line_count: &mut usize) -> Result<P
     let meta_version = {
         let tokens = get_meta_line("VERSION")?;
         if tokens.len() == 2 {
+            // Keep the version as written to reproduce the header
             match tokens[1].as_str() {
                 "0.7" => String::from("0.7"),
-                ".7" => String::from("0.7"),
+                ".7" => String::from(".7"),
                 _ => {
                     let desc = format!(
                         "Unsupported version {:?}. Supported versions are: 0.7",
@@ -78,12 +98,13 @@ pub fn load_meta<R: BufRead>(reader: &mut R, line_count: &mut usize) -> Result<P
         let mut name_set = HashSet::new();
         let mut field_names: Vec<String> = vec![];
 
-        for (idx, tk) in tokens[1..].iter().enumerate() {
-            let mut field = tk.clone();
-            // If this field is just an underscore, it was meant to be skipped. Label it as
-            // unknown_field_{idx}
-            if field == String::from("_") {
-                field = format!("unknown_field_{idx}");
+        for tk in tokens[1..].iter() {
+            let field = tk.clone();
+
+            // Padding fields are allowed to appear more than once.
+            if field == FieldDef::PADDING_NAME {
+                field_names.push(field);
+                continue;
             }
 
             if name_set.contains(&field.clone()) {
@@ -254,6 +275,7 @@ pub fn load_meta<R: BufRead>(reader: &mut R, line_count: &mut usize) -> Result<P
     }
 
     // Organize field type
+    let mut offset = 0;
     let field_defs: Result<Schema> = {
         meta_fields
             .iter()
@@ -261,16 +283,9 @@ pub fn load_meta<R: BufRead>(reader: &mut R, line_count: &mut usize) -> Result<P
             .zip(meta_size.iter())
             .zip(meta_count.iter())
             .map(|(((name, type_), size), &count)| {
-                let kind = match (type_, size) {
-                    (TypeKind::U, 1) => ValueKind::U8,
-                    (TypeKind::U, 2) => ValueKind::U16,
-                    (TypeKind::U, 4) => ValueKind::U32,
-                    (TypeKind::I, 1) => ValueKind::I8,
-                    (TypeKind::I, 2) => ValueKind::I16,
-                    (TypeKind::I, 4) => ValueKind::I32,
-                    (TypeKind::F, 4) => ValueKind::F32,
-                    (TypeKind::F, 8) => ValueKind::F64,
-                    _ => {
+                let kind = match ValueKind::from_type_size(*type_, *size) {
+                    Some(kind) => kind,
+                    None => {
                         let desc =
                             format!("Field type {:?} with size {} is not supported", type_, size);
                         return Err(Error::new_parse_error(*line_count, &desc).into());
@@ -281,7 +296,10 @@ pub fn load_meta<R: BufRead>(reader: &mut R, line_count: &mut usize) -> Result<P
                     name: name.to_owned(),
                     kind,
                     count,
+                    offset,
+                    padding: name == FieldDef::PADDING_NAME,
                 };
+                offset += size * count;
 
                 Ok(meta)
             })
@@ -290,6 +308,7 @@ pub fn load_meta<R: BufRead>(reader: &mut R, line_count: &mut usize) -> Result<P
 
     let meta = PcdMeta {
         version: meta_version,
+        comments,
         field_defs: field_defs?,
         width: meta_width,
         height: meta_height,
@@ -300,3 +319,162 @@ pub fn load_meta<R: BufRead>(reader: &mut R, line_count: &mut usize) -> Result<P
 
     Ok(meta)
 }
+
+/// Writes the PCD header, where the WIDTH and POINTS entries are
+/// padded to `width_arg_width` and `points_arg_width` characters, and
+/// the VIEWPOINT entry is written in `viewpoint_format`. It returns
+/// the byte offsets of the WIDTH and POINTS arguments relative to the
+/// start of the header.
+pub fn write_meta<W: Write>(
+    writer: &mut W,
+    meta: &PcdMeta,
+    width_arg_width: usize,
+    points_arg_width: usize,
+    viewpoint_format: FloatFormat,
+) -> Result<(u64, u64)> {
+    use std::fmt::Write as _;
+
+    let PcdMeta {
+        version,
+        comments,
+        width,
+        height,
+        viewpoint,
+        num_points,
+        data,
+        field_defs,
+    } = meta;
+
+    let fields_args: Vec<_> = field_defs.iter().map(|field| field.name.as_str()).collect();
+
+    let size_args: Vec<_> = field_defs
+        .iter()
+        .map(|field| field.kind.size().to_string())
+        .collect();
+
+    let type_args: Vec<_> = field_defs
+        .iter()
+        .map(|field| match field.kind.type_kind() {
+            TypeKind::U => "U",
+            TypeKind::I => "I",
+            TypeKind::F => "F",
+        })
+        .collect();
+
+    let count_args: Vec<_> = field_defs
+        .iter()
+        .map(|field| field.count.to_string())
+        .collect();
+
+    let viewpoint_args: Vec<_> = [
+        viewpoint.tx,
+        viewpoint.ty,
+        viewpoint.tz,
+        viewpoint.qw,
+        viewpoint.qx,
+        viewpoint.qy,
+        viewpoint.qz,
+    ]
+    .iter()
+    .map(|&value| viewpoint_format.format(value))
+    .collect();
+
+    let data_arg = match data {
+        DataKind::Binary => "binary",
+        DataKind::Ascii => "ascii",
+    };
+
+    let mut header = String::new();
+    for comment in comments {
+        if comment.contains(['\n', '\r']) {
+            let desc = format!("comment {:?} must not contain line breaks", comment);
+            return Err(Error::new_invalid_argument_error(&desc).into());
+        }
+        writeln!(header, "#{}", comment)?;
+    }
+    writeln!(header, "VERSION {}", version)?;
+    writeln!(header, "FIELDS {}", fields_args.join(" "))?;
+    writeln!(header, "SIZE {}", size_args.join(" "))?;
+    writeln!(header, "TYPE {}", type_args.join(" "))?;
+    writeln!(header, "COUNT {}", count_args.join(" "))?;
+    write!(header, "WIDTH ")?;
+    let width_arg_begin = header.len() as u64;
+    writeln!(header, "{:<width$}", width, width = width_arg_width)?;
+    writeln!(header, "HEIGHT {}", height)?;
+    writeln!(header, "VIEWPOINT {}", viewpoint_args.join(" "))?;
+    write!(header, "POINTS ")?;
+    let points_arg_begin = header.len() as u64;
+    writeln!(header, "{:<width$}", num_points, width = points_arg_width)?;
+    writeln!(header, "DATA {}", data_arg)?;
+
+    writer.write_all(header.as_bytes())?;
+    Ok((width_arg_begin, points_arg_begin))
+}
+
+/// Finds the argument of a header entry. It returns the byte offset of
+/// the argument in the header and the number of bytes available for
+/// the argument until the line or an inline comment ends.
+pub fn find_header_arg(header: &str, entry: &str) -> Option<(u64, usize)> {
+    let mut line_begin = 0;
+
+    for line in header.split_inclusive('\n') {
+        let content = line
+            .split('#')
+            .next()
+            .unwrap_or("")
+            .trim_end_matches(['\r', '\n']);
+        let mut tokens = content.split_ascii_whitespace();
+
+        if tokens.next() == Some(entry) && tokens.next().is_some() && tokens.next().is_none() {
+            let entry_end = content.find(entry)? + entry.len();
+            let arg_begin = content.len() - content[entry_end..].trim_start().len();
+            return Some(((line_begin + arg_begin) as u64, content.len() - arg_begin));
+        }
+
+        line_begin += line.len();
+    }
+
+    None
+}
+
+/// Finds the indices of the x, y and z fields that exist in the schema.
+pub fn find_xyz_fields(schema: &Schema) -> Vec<usize> {
+    ["x", "y", "z"]
+        .iter()
+        .filter_map(|name| schema.index_of(name))
+        .collect()
+}
+
+/// Checks if the values of given fields in a binary record are finite.
+pub fn is_chunk_finite(chunk: &[u8], schema: &Schema, fields: &[usize]) -> bool {
+    fields.iter().all(|&index| {
+        let def = &schema[index];
+        let begin = def.offset as usize;
+        let values = &chunk[begin..(begin + def.size())];
+
+        match def.kind {
+            ValueKind::F32 => values
+                .chunks_exact(4)
+                .all(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()).is_finite()),
+            ValueKind::F64 => values
+                .chunks_exact(8)
+                .all(|bytes| f64::from_le_bytes(bytes.try_into().unwrap()).is_finite()),
+            _ => true,
+        }
+    })
+}
+
+/// Checks if the values of given fields in an Ascii record are finite.
+/// Tokens that are not numbers are left for the record parser to report.
+pub fn is_line_finite(line: &[u8], schema: &Schema, fields: &[usize]) -> bool {
+    fields.iter().all(|&index| {
+        let def = &schema[index];
+        let token_index: u64 = schema.iter().take(index).map(|def| def.count).sum();
+
+        Tokens::new(line)
+            .skip(token_index as usize)
+            .take(def.count as usize)
+            .filter_map(f64::from_token)
+            .all(f64::is_finite)
+    })
+}
@@ -21,11 +21,12 @@ pub struct Point {
 
 fn main() -> Result<()> {
     let mut writer: Writer<Point, _> = WriterInit {
-        height: 300,
+        height: 1,
         width: 1,
         viewpoint: Default::default(),
         data_kind: DataKind::Ascii,
         schema: None,
+        ..Default::default()
     }
     .create("test_files/dump.pcd")?;
 
@@ -47,31 +48,156 @@ fn main() -> Result<()> {
 )]
 
 use crate::{
-    metas::{DataKind, FieldDef, Schema, ValueKind, ViewPoint},
-    record::{DynRecord, PcdSerialize},
+    error::Error,
+    metas::{DataKind, PcdMeta, Schema, ViewPoint},
+    record::{matches_static_fields, DynRecord, PcdColumns, PcdSerialize},
 };
 use anyhow::{bail, ensure, Result};
+use num_traits::Float;
 use std::{
-    collections::HashSet,
-    fs::File,
-    io::{prelude::*, BufWriter, SeekFrom},
+    borrow::Borrow,
+    fmt::{Display, LowerExp},
+    fs::{self, File, OpenOptions},
+    io::{prelude::*, BufWriter, Cursor, ErrorKind, SeekFrom},
     marker::PhantomData,
     path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
 /// The `DynReader` struct writes points with schema determined in runtime.
 pub type DynWriter<W> = Writer<DynRecord, W>;
 
+/// The format of floating point numbers written as text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FloatFormat {
+    /// The shortest digits that read back to the same value.
+    #[default]
+    Shortest,
+    /// The number of digits after the decimal point.
+    Fixed(usize),
+    /// The scientific notation with the number of digits after the
+    /// decimal point, such as `1.50e-3`.
+    Scientific(usize),
+}
+
+impl FloatFormat {
+    /// Formats a number. Like PCL, NaN is written as `nan`, and
+    /// infinities are written as `inf` and `-inf`.
+    pub fn format<T>(&self, value: T) -> String
+    where
+        T: Float + Display + LowerExp,
+    {
+        if value.is_nan() {
+            return "nan".to_string();
+        }
+        if value.is_infinite() {
+            return value.to_string();
+        }
+
+        match *self {
+            Self::Shortest => value.to_string(),
+            Self::Fixed(precision) => format!("{:.*}", precision, value),
+            Self::Scientific(precision) => format!("{:.*e}", precision, value),
+        }
+    }
+}
+
+/// The number formatting options for text written by [Writer].
+///
+/// The `float` format applies to Ascii data, except the named fields
+/// in `fields`. The `viewpoint` format applies to the VIEWPOINT entry.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AsciiFormat {
+    pub float: FloatFormat,
+    pub fields: Vec<(String, FloatFormat)>,
+    pub viewpoint: FloatFormat,
+}
+
+impl AsciiFormat {
+    /// Gets the float format of each field in the schema.
+    pub fn field_formats(&self, schema: &Schema) -> Result<Vec<FloatFormat>> {
+        if let Some((name, _)) = self
+            .fields
+            .iter()
+            .find(|(name, _)| schema.field(name).is_none())
+        {
+            return Err(Error::new_field_not_found_error(name).into());
+        }
+
+        let formats = schema
+            .iter()
+            .map(|def| {
+                self.fields
+                    .iter()
+                    .find(|(name, _)| *name == def.name)
+                    .map_or(self.float, |&(_, format)| format)
+            })
+            .collect();
+        Ok(formats)
+    }
+}
+
 /// A builder type that builds [Writer](crate::writer::Writer).
+///
+/// The `version` is written to the VERSION entry as is, and each of
+/// `comments` is written after `#` as a comment line at the top. If
+/// `num_points` is set, the POINTS entry is written exactly with the
+/// number, and the number of pushed points must not take more digits.
+/// Otherwise, the POINTS entry is padded with spaces and filled when
+/// the writer finishes. If `width` is 0, the WIDTH entry is filled
+/// with the number of points per row in the same way. Otherwise,
+/// `width` is written as is, and `width * height` must equal the
+/// number of pushed points. The `ascii_format` sets how numbers are
+/// written in Ascii data and the VIEWPOINT entry.
+#[derive(Debug, Clone)]
 pub struct WriterInit {
     pub width: u64,
     pub height: u64,
     pub viewpoint: ViewPoint,
     pub data_kind: DataKind,
     pub schema: Option<Schema>,
+    pub version: String,
+    pub comments: Vec<String>,
+    pub num_points: Option<u64>,
+    pub ascii_format: AsciiFormat,
+}
+
+impl Default for WriterInit {
+    fn default() -> Self {
+        Self {
+            width: 0,
+            height: 1,
+            viewpoint: ViewPoint::default(),
+            data_kind: DataKind::Binary,
+            schema: None,
+            version: ".7".to_string(),
+            comments: vec![" .PCD v.7 - Point Cloud Data file format".to_string()],
+            num_points: None,
+            ascii_format: AsciiFormat::default(),
+        }
+    }
 }
 
 impl WriterInit {
+    /// Creates a builder that writes the same header as the meta data.
+    ///
+    /// The schema is set to `meta.field_defs`, which is checked
+    /// against the record type when the writer is built. All comments,
+    /// including inline ones, are written as leading comment lines.
+    pub fn from_meta(meta: &PcdMeta) -> Self {
+        Self {
+            width: meta.width,
+            height: meta.height,
+            viewpoint: meta.viewpoint.clone(),
+            data_kind: meta.data,
+            schema: Some(meta.field_defs.clone()),
+            version: meta.version.clone(),
+            comments: meta.comments.clone(),
+            num_points: Some(meta.num_points),
+            ascii_format: AsciiFormat::default(),
+        }
+    }
+
     /// Builds new [Writer](crate::writer::Writer) object from a writer.
     /// The writer must implement both [Write](std::io::Write) and [Write](std::io::Seek)
     /// traits.
@@ -79,32 +205,34 @@ impl WriterInit {
         self,
         writer: W,
     ) -> Result<Writer<Record, W>> {
-        let record_spec = match (Record::is_dynamic(), self.schema) {
+        let schema = match (Record::is_dynamic(), self.schema) {
             (true, Some(schema)) => {
-                let names: Result<HashSet<_>> = schema
-                    .iter()
-                    .cloned()
-                    .map(|FieldDef { name, count, .. }| {
-                        ensure!(!name.is_empty(), "field name must not be empty");
-                        ensure!(count > 0, "count must not be zero");
-                        Ok(name)
-                    })
-                    .collect();
-                ensure!(names?.len() == schema.len(), "schema names must be unique");
+                schema.validate()?;
                 schema
             }
             (true, None) => bail!("schema is not set "),
-            (false, Some(_schema)) => bail!("schema must not be set for static record type"),
-            (false, None) => Record::write_spec(),
+            (false, Some(schema)) => {
+                check_write_spec::<Record>(&schema)?;
+                schema
+            }
+            (false, None) => {
+                // Fails if the length of a Vec field is not known
+                let schema = Record::write_spec();
+                schema.validate()?;
+                schema
+            }
         };
-        let seq_writer = Writer::new(
-            self.width,
-            self.height,
-            self.data_kind,
-            self.viewpoint,
-            record_spec,
-            writer,
-        )?;
+        let meta = PcdMeta {
+            version: self.version,
+            comments: self.comments,
+            width: self.width,
+            height: self.height,
+            viewpoint: self.viewpoint,
+            num_points: self.num_points.unwrap_or(0),
+            data: self.data_kind,
+            field_defs: schema,
+        };
+        let seq_writer = Writer::new(meta, self.num_points.is_some(), &self.ascii_format, writer)?;
         Ok(seq_writer)
     }
 
@@ -126,11 +254,19 @@ where
     W: Write + Seek,
 {
     data_kind: DataKind,
+    schema: Schema,
     record_spec: Schema,
+    float_formats: Vec<FloatFormat>,
     writer: W,
     num_records: usize,
     points_arg_begin: u64,
     points_arg_width: usize,
+    width_arg_begin: Option<u64>,
+    width_arg_width: usize,
+    width: u64,
+    height: u64,
+    data_buffer: Cursor<Vec<u8>>,
+    chunk_buffer: Vec<u8>,
     finished: bool,
     _phantom: PhantomData<T>,
 }
@@ -141,96 +277,72 @@ where
     W: Write + Seek,
 {
     fn new(
-        width: u64,
-        height: u64,
-        data_kind: DataKind,
-        viewpoint: ViewPoint,
-        record_spec: Schema,
+        meta: PcdMeta,
+        exact_points: bool,
+        ascii_format: &AsciiFormat,
         mut writer: W,
     ) -> Result<Self> {
-        let (points_arg_begin, points_arg_width) = {
-            let fields_args: Vec<_> = record_spec
-                .iter()
-                .map(|field| field.name.to_owned())
-                .collect();
+        let data_kind = meta.data;
+        let schema = meta.field_defs;
+        let record_spec = schema.without_padding();
+        let float_formats = ascii_format.field_formats(&record_spec)?;
 
-            let size_args: Vec<_> = record_spec
-                .iter()
-                .map(|field| {
-                    use ValueKind::*;
-                    let size = match field.kind {
-                        U8 | I8 => 1,
-                        U16 | I16 => 2,
-                        U32 | I32 | F32 => 4,
-                        F64 => 8,
-                    };
-                    size.to_string()
-                })
-                .collect();
-
-            let type_args: Vec<_> = record_spec
-                .iter()
-                .map(|field| {
-                    use ValueKind::*;
-                    match field.kind {
-                        U8 | U16 | U32 => "U",
-                        I8 | I16 | I32 => "I",
-                        F32 | F64 => "F",
-                    }
-                })
-                .collect();
-
-            let count_args: Vec<_> = record_spec
-                .iter()
-                .map(|field| field.count.to_string())
-                .collect();
-
-            let viewpoint_args: Vec<_> = {
-                [
-                    viewpoint.tx,
-                    viewpoint.ty,
-                    viewpoint.tz,
-                    viewpoint.qw,
-                    viewpoint.qx,
-                    viewpoint.qy,
-                    viewpoint.qz,
-                ]
-                .iter()
-                .map(|value| value.to_string())
-                .collect()
-            };
-
-            let points_arg_width = (usize::max_value() as f64).log10().floor() as usize + 1;
-
-            writeln!(writer, "# .PCD v.7 - Point Cloud Data file format")?;
-            writeln!(writer, "VERSION .7")?;
-            writeln!(writer, "FIELDS {}", fields_args.join(" "))?;
-            writeln!(writer, "SIZE {}", size_args.join(" "))?;
-            writeln!(writer, "TYPE {}", type_args.join(" "))?;
-            writeln!(writer, "COUNT {}", count_args.join(" "))?;
-            writeln!(writer, "WIDTH {}", width)?;
-            writeln!(writer, "HEIGHT {}", height)?;
-            writeln!(writer, "VIEWPOINT {}", viewpoint_args.join(" "))?;
-
-            write!(writer, "POINTS ")?;
-            let points_arg_begin = writer.seek(SeekFrom::Current(0))?;
-            writeln!(writer, "{:width$}", " ", width = points_arg_width)?;
-
-            match data_kind {
-                DataKind::Binary => writeln!(writer, "DATA binary")?,
-                DataKind::Ascii => writeln!(writer, "DATA ascii")?,
-            }
+        // Like PCL, padding fields are not written in Ascii data
+        let header_meta = PcdMeta {
+            field_defs: match data_kind {
+                DataKind::Binary => schema.clone(),
+                DataKind::Ascii => record_spec.clone(),
+            },
+            ..meta
+        };
 
-            (points_arg_begin, points_arg_width)
+        // The POINTS entry is filled with spaces to leave room for the
+        // final number of points, unless the number is given in advance.
+        let points_arg_width = if exact_points {
+            header_meta.num_points.to_string().len()
+        } else {
+            padded_arg_width()
+        };
+        // A WIDTH of 0 is filled with the number of points per row like
+        // POINTS. Otherwise, it is written as given.
+        let fill_width = header_meta.width == 0;
+        let width_arg_width = if !fill_width {
+            header_meta.width.to_string().len()
+        } else if exact_points && header_meta.height > 0 {
+            (header_meta.num_points / header_meta.height)
+                .to_string()
+                .len()
+        } else {
+            padded_arg_width()
         };
+        let (width, height) = (header_meta.width, header_meta.height);
+
+        let header_begin = writer.stream_position()?;
+        let (width_arg_offset, points_arg_offset) = crate::utils::write_meta(
+            &mut writer,
+            &header_meta,
+            width_arg_width,
+            points_arg_width,
+            ascii_format.viewpoint,
+        )?;
+        let width_arg_begin = fill_width.then_some(header_begin + width_arg_offset);
+        let points_arg_begin = header_begin + points_arg_offset;
 
         let seq_writer = Self {
             data_kind,
+            schema,
             record_spec,
+            float_formats,
             writer,
             num_records: 0,
             points_arg_begin,
             points_arg_width,
+            width_arg_begin,
+            width_arg_width,
+            width,
+            height,
+            data_buffer: Cursor::new(vec![]),
+            chunk_buffer: vec![],
             finished: false,
             _phantom: PhantomData,
         };
@@ -242,6 +354,49 @@ where
     /// The method consumes the writer must be called once when finished.
     /// Otherwise it will panic when it drops.
     pub fn finish(mut self) -> Result<()> {
+        self.finished = true;
+
+        let num_records = self.num_records.to_string();
+        ensure!(
+            num_records.len() <= self.points_arg_width,
+            "{} points are written, but the POINTS entry only has room for {} digits",
+            num_records,
+            self.points_arg_width
+        );
+
+        let num_points = self.num_records as u64;
+        if let Some(width_arg_begin) = self.width_arg_begin {
+            ensure!(
+                num_points.checked_rem(self.height) == Some(0),
+                "{} points are written, but they cannot fill {} rows",
+                num_points,
+                self.height
+            );
+            let width = (num_points / self.height).to_string();
+            ensure!(
+                width.len() <= self.width_arg_width,
+                "the width {} is written, but the WIDTH entry only has room for {} digits",
+                width,
+                self.width_arg_width
+            );
+
+            self.writer.seek(SeekFrom::Start(width_arg_begin))?;
+            write!(
+                self.writer,
+                "{:<width$}",
+                width,
+                width = self.width_arg_width
+            )?;
+        } else {
+            ensure!(
+                self.width.checked_mul(self.height) == Some(num_points),
+                "{} points are written, but WIDTH {} and HEIGHT {} do not match",
+                num_points,
+                self.width,
+                self.height
+            );
+        }
+
         self.writer.seek(SeekFrom::Start(self.points_arg_begin))?;
         write!(
             self.writer,
@@ -249,20 +404,318 @@ where
             self.num_records,
             width = self.points_arg_width
         )?;
-        self.finished = true;
+        self.writer.flush()?;
         Ok(())
     }
 
     /// Writes a new point to PCD data.
     pub fn push(&mut self, record: &Record) -> Result<()> {
         match self.data_kind {
+            DataKind::Binary if self.schema.has_padding() => self.write_padded_chunk(record)?,
             DataKind::Binary => record.write_chunk(&mut self.writer, &self.record_spec)?,
-            DataKind::Ascii => record.write_line(&mut self.writer, &self.record_spec)?,
+            DataKind::Ascii => {
+                record.write_line_with(&mut self.writer, &self.record_spec, &self.float_formats)?
+            }
         }
 
         self.num_records += 1;
         Ok(())
     }
+
+    /// Writes a slice of points to PCD data.
+    ///
+    /// Binary records are written in bulk by
+    /// [write_chunks()](PcdSerialize::write_chunks) unless the schema
+    /// has padding fields.
+    pub fn push_all(&mut self, records: &[Record]) -> Result<()> {
+        match self.data_kind {
+            DataKind::Binary if self.schema.has_padding() => records
+                .iter()
+                .try_for_each(|record| self.write_padded_chunk(record))?,
+            DataKind::Binary => Record::write_chunks(records, &mut self.writer, &self.record_spec)?,
+            DataKind::Ascii => records.iter().try_for_each(|record| {
+                record.write_line_with(&mut self.writer, &self.record_spec, &self.float_formats)
+            })?,
+        }
+
+        self.num_records += records.len();
+        Ok(())
+    }
+
+    /// Writes the points in the columns to PCD data without building
+    /// records.
+    pub fn push_columns<C>(&mut self, columns: &C) -> Result<()>
+    where
+        C: PcdColumns<Record = Record>,
+    {
+        for index in 0..columns.len() {
+            match self.data_kind {
+                DataKind::Binary if self.schema.has_padding() => self
+                    .write_padded_chunk_with(|data, spec| columns.write_chunk(index, data, spec))?,
+                DataKind::Binary => {
+                    columns.write_chunk(index, &mut self.writer, &self.record_spec)?
+                }
+                DataKind::Ascii => columns.write_line(
+                    index,
+                    &mut self.writer,
+                    &self.record_spec,
+                    &self.float_formats,
+                )?,
+            }
+            self.num_records += 1;
+        }
+        Ok(())
+    }
+
+    /// Writes points from an iterator to PCD data.
+    pub fn extend<I>(&mut self, records: I) -> Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Record>,
+    {
+        records
+            .into_iter()
+            .try_for_each(|record| self.push(record.borrow()))
+    }
+}
+
+impl<Record> Writer<Record, BufWriter<File>>
+where
+    Record: PcdSerialize,
+{
+    /// Opens an existing PCD file to append points after its data.
+    ///
+    /// The header is checked against the record type, and only point
+    /// clouds with HEIGHT 1 can be extended. Bytes after the binary
+    /// data are discarded.
+    ///
+    /// The POINTS and WIDTH entries are updated in place when the writer
+    /// finishes, so it only works on files whose POINTS and WIDTH
+    /// arguments are padded with spaces to 20 characters, which [Writer]
+    /// does when `width` is 0 and `num_points` is not given. Most files
+    /// written by other tools, like PCL, are not padded and fail to
+    /// open. Use [rewrite_and_append_to()](Self::rewrite_and_append_to)
+    /// for such files.
+    pub fn append_to<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Self::open_append(path.as_ref(), false)
+    }
+
+    /// Like [append_to()](Self::append_to), but if the POINTS or WIDTH
+    /// entry has no room for a padded number, the whole file is first
+    /// copied to a new temporary file with a padded header, which then
+    /// replaces the file.
+    pub fn rewrite_and_append_to<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Self::open_append(path.as_ref(), true)
+    }
+
+    fn open_append(path: &Path, rewrite: bool) -> Result<Self> {
+        let (meta, data_offset) = PcdMeta::from_path(path)?;
+
+        ensure!(
+            meta.height == 1,
+            "cannot append to an organized point cloud with height {}",
+            meta.height
+        );
+        ensure!(
+            meta.data == DataKind::Binary || !meta.field_defs.has_padding(),
+            "cannot append to Ascii data with padding fields"
+        );
+        if !Record::is_dynamic() {
+            check_write_spec::<Record>(&meta.field_defs)?;
+        }
+
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+        let mut header = vec![0; data_offset as usize];
+        file.read_exact(&mut header)?;
+        let mut header = String::from_utf8(header)?;
+
+        // The data size does not overflow as checked by from_path()
+        let data_size = match meta.data {
+            DataKind::Binary => meta.num_points * meta.field_defs.record_size_bytes() as u64,
+            DataKind::Ascii => file.metadata()?.len() - data_offset,
+        };
+
+        let arg_width = padded_arg_width();
+        let find_room = |header: &str, entry| -> Result<(u64, usize)> {
+            crate::utils::find_header_arg(header, entry)
+                .filter(|&(_, width)| width >= arg_width)
+                .ok_or_else(|| {
+                    let desc = format!("the {} entry has no room for {} digits", entry, arg_width);
+                    Error::new_invalid_argument_error(&desc).into()
+                })
+        };
+
+        // Make room for padded WIDTH and POINTS arguments by moving the
+        // data after a new header.
+        let has_room = find_room(&header, "WIDTH").is_ok() && find_room(&header, "POINTS").is_ok();
+        if rewrite && !has_room {
+            let mut new_header = vec![];
+            crate::utils::write_meta(
+                &mut new_header,
+                &meta,
+                arg_width,
+                arg_width,
+                FloatFormat::default(),
+            )?;
+
+            let (tmp_path, tmp_file) = create_temp_file(path)?;
+            let result = (|| -> Result<()> {
+                let mut tmp_file = BufWriter::new(tmp_file);
+                tmp_file.write_all(&new_header)?;
+                file.seek(SeekFrom::Start(data_offset))?;
+                std::io::copy(&mut (&mut file).take(data_size), &mut tmp_file)?;
+                tmp_file.flush()?;
+                fs::rename(&tmp_path, path)?;
+                Ok(())
+            })();
+            if let Err(error) = result {
+                let _ = fs::remove_file(&tmp_path);
+                return Err(error);
+            }
+
+            file = OpenOptions::new().read(true).write(true).open(path)?;
+            header = String::from_utf8(new_header)?;
+        }
+
+        let (width_arg_begin, width_arg_width) = find_room(&header, "WIDTH")?;
+        let (points_arg_begin, points_arg_width) = find_room(&header, "POINTS")?;
+        let data_end = header.len() as u64 + data_size;
+        file.set_len(data_end)?;
+
+        // Ascii data may miss the line break after the last point
+        if meta.data == DataKind::Ascii && data_size > 0 {
+            let mut last = [0];
+            file.seek(SeekFrom::Start(data_end - 1))?;
+            file.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                file.write_all(b"\n")?;
+            }
+        }
+        file.seek(SeekFrom::End(0))?;
+
+        let record_spec = meta.field_defs.without_padding();
+        let float_formats = vec![FloatFormat::default(); record_spec.len()];
+        Ok(Self {
+            data_kind: meta.data,
+            schema: meta.field_defs,
+            record_spec,
+            float_formats,
+            writer: BufWriter::new(file),
+            num_records: meta.num_points as usize,
+            points_arg_begin,
+            points_arg_width,
+            width_arg_begin: Some(width_arg_begin),
+            width_arg_width,
+            width: meta.width,
+            height: meta.height,
+            data_buffer: Cursor::new(vec![]),
+            chunk_buffer: vec![],
+            finished: false,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<W, Record> Writer<Record, W>
+where
+    Record: PcdSerialize,
+    W: Write + Seek,
+{
+    /// Writes a binary record with zero bytes filled in padding fields.
+    fn write_padded_chunk(&mut self, record: &Record) -> Result<()> {
+        self.write_padded_chunk_with(|data, spec| record.write_chunk(data, spec))
+    }
+
+    /// Writes a binary record written by `write` with zero bytes filled
+    /// in padding fields.
+    fn write_padded_chunk_with<F>(&mut self, write: F) -> Result<()>
+    where
+        F: FnOnce(&mut Cursor<Vec<u8>>, &Schema) -> Result<()>,
+    {
+        let data = &mut self.data_buffer;
+        data.get_mut().clear();
+        data.set_position(0);
+        write(data, &self.record_spec)?;
+        let data = data.get_ref();
+
+        let chunk = &mut self.chunk_buffer;
+        chunk.clear();
+        let mut data_offset = 0;
+
+        for def in self.schema.iter() {
+            let size = def.size();
+            if def.padding {
+                chunk.resize(chunk.len() + size, 0);
+            } else {
+                chunk.extend_from_slice(&data[data_offset..(data_offset + size)]);
+                data_offset += size;
+            }
+        }
+
+        self.writer.write_all(chunk)?;
+        Ok(())
+    }
+}
+
+/// Checks the schema against the static record type except padding.
+/// Fields with zero count in the write spec accept any count, and
+/// positional records accept any names.
+fn check_write_spec<Record: PcdSerialize>(schema: &Schema) -> Result<()> {
+    if let Some(fields) = Record::WRITE_FIELDS {
+        ensure!(
+            matches_static_fields(fields, schema),
+            "schema does not match the static record type except padding"
+        );
+        return Ok(());
+    }
+
+    let write_spec = Record::write_spec();
+    let data_spec = schema.without_padding();
+    let is_positional = Record::is_positional();
+    let is_consistent = data_spec.len() == write_spec.len()
+        && data_spec.iter().zip(write_spec.iter()).all(|(lhs, rhs)| {
+            (is_positional || lhs.name == rhs.name)
+                && lhs.kind == rhs.kind
+                && (rhs.count == 0 || lhs.count == rhs.count)
+        });
+    ensure!(
+        is_consistent,
+        "schema does not match the static record type except padding"
+    );
+    Ok(())
+}
+
+/// Creates a new file next to `path` to be renamed to `path`. The name
+/// is made unique by the process ID and a counter.
+fn create_temp_file(path: &Path) -> Result<(std::ffi::OsString, File)> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    loop {
+        let mut tmp_path = path.as_os_str().to_owned();
+        let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+        tmp_path.push(format!(".{}.{}.tmp", std::process::id(), count));
+
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&tmp_path)
+        {
+            Ok(file) => return Ok((tmp_path, file)),
+            Err(error) if error.kind() == ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error.into()),
+        }
+    }
+}
+
+/// The number of characters reserved for a POINTS argument filled later.
+fn padded_arg_width() -> usize {
+    usize::MAX.to_string().len()
 }
 
 impl<W, Record> Drop for Writer<Record, W>
@@ -0,0 +1,103 @@
+use anyhow::Result;
+use itertools::Itertools as _;
+use pcd_rs::{DynReader, DynRecord, DynWriter, PcdMeta, WriterInit};
+use std::fs;
+
+fn append_twice(path: &str, rewrite: bool) -> Result<()> {
+    let points: Vec<DynRecord> = DynReader::open(path)?.try_collect()?;
+
+    let mut writer = if rewrite {
+        DynWriter::rewrite_and_append_to(path)?
+    } else {
+        DynWriter::append_to(path)?
+    };
+    for point in &points[..10] {
+        writer.push(point)?;
+    }
+    writer.finish()?;
+    let header_size = PcdMeta::from_path(path)?.1;
+
+    // The second append updates the padded header in place
+    let mut writer = DynWriter::append_to(path)?;
+    writer.push(&points[0])?;
+    writer.finish()?;
+
+    let (meta, data_offset) = PcdMeta::from_path(path)?;
+    assert_eq!(data_offset, header_size);
+    assert_eq!(meta.num_points as usize, points.len() + 11);
+    assert_eq!(meta.width, meta.num_points);
+
+    let new_points: Vec<DynRecord> = DynReader::open(path)?.try_collect()?;
+    assert_eq!(new_points[..points.len()], points);
+    assert_eq!(new_points[points.len()..(points.len() + 10)], points[..10]);
+    assert_eq!(new_points.last(), points.first());
+
+    Ok(())
+}
+
+#[test]
+fn append_binary() -> Result<()> {
+    let path = "test_files/dump_append_binary.pcd";
+    let reader = DynReader::open("test_files/binary.pcd")?;
+    // The header is padded as the shape is not given
+    let init = WriterInit {
+        width: 0,
+        height: 1,
+        num_points: None,
+        ..WriterInit::from_meta(reader.meta())
+    };
+    let mut writer = init.create(path)?;
+    for point in reader {
+        writer.push(&point?)?;
+    }
+    writer.finish()?;
+
+    let result = append_twice(path, false);
+    fs::remove_file(path)?;
+    result
+}
+
+#[test]
+fn append_ascii() -> Result<()> {
+    let path = "test_files/dump_append_ascii.pcd";
+    fs::copy("test_files/ascii.pcd", path)?;
+
+    // The header has no room for more points unless rewritten
+    let result = DynWriter::append_to(path)
+        .err()
+        .ok_or_else(|| anyhow::anyhow!("unpadded header is accepted"))
+        .and_then(|_| append_twice(path, true));
+    fs::remove_file(path)?;
+    result
+}
+
+fn append_rewritten(path: &str) -> Result<()> {
+    let points: Vec<DynRecord> = DynReader::open(path)?.try_collect()?;
+    let old_header_size = PcdMeta::from_path(path)?.1;
+
+    let mut writer = DynWriter::rewrite_and_append_to(path)?;
+    writer.push(&points[0])?;
+    writer.finish()?;
+
+    // The header is rewritten with padded POINTS and WIDTH
+    let (meta, header_size) = PcdMeta::from_path(path)?;
+    assert!(header_size > old_header_size);
+    assert_eq!(meta.num_points as usize, points.len() + 1);
+    assert_eq!(meta.width, meta.num_points);
+
+    let new_points: Vec<DynRecord> = DynReader::open(path)?.try_collect()?;
+    assert_eq!(new_points[..points.len()], points);
+    assert_eq!(new_points.last(), points.first());
+
+    Ok(())
+}
+
+#[test]
+fn rewrite_unpadded_ascii() -> Result<()> {
+    let path = "test_files/dump_rewrite_ascii.pcd";
+    fs::copy("test_files/ascii.pcd", path)?;
+
+    let result = append_rewritten(path);
+    fs::remove_file(path)?;
+    result
+}
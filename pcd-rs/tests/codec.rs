@@ -0,0 +1,155 @@
+#![cfg(feature = "derive")]
+
+mod common;
+
+use anyhow::Result;
+use itertools::Itertools as _;
+use pcd_rs::{codec, DataKind, Error, PcdDeserialize, PcdSerialize, Reader, ValueKind, WriterInit};
+use std::io::Cursor;
+
+#[derive(Debug, Clone, PartialEq, PcdDeserialize, PcdSerialize)]
+pub struct Point {
+    #[pcd(as = "f32")]
+    x: f64,
+    #[pcd(as = "f32")]
+    y: f64,
+    #[pcd(as = "f32")]
+    z: f64,
+    // The Ascii test file writes colors as integers
+    #[pcd(as = "f32")]
+    rgb: u32,
+}
+
+#[derive(Debug, PcdDeserialize)]
+pub struct RawPoint {
+    x: f32,
+    y: f32,
+    z: f32,
+    rgb: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, PcdDeserialize, PcdSerialize)]
+pub struct ColorPoint {
+    #[pcd(as = "i16")]
+    x: [i32; 2],
+    #[pcd(with = "codec::rgb")]
+    rgb: [u8; 3],
+    #[pcd(with = "pcd_rs::codec::rgba")]
+    rgba: [u8; 4],
+}
+
+#[derive(Debug, PcdDeserialize)]
+pub struct NarrowPoint {
+    #[pcd(as = "i16")]
+    pub x: [i8; 2],
+    #[pcd(with = "codec::rgb")]
+    pub rgb: [u8; 3],
+    #[pcd(with = "codec::rgba")]
+    pub rgba: [u8; 4],
+}
+
+#[test]
+fn read_converted_fields() -> Result<()> {
+    let raw_points: Vec<RawPoint> = Reader::open("test_files/ascii.pcd")?.try_collect()?;
+    let points: Vec<Point> = Reader::open("test_files/ascii.pcd")?.try_collect()?;
+
+    assert_eq!(points.len(), raw_points.len());
+    for (point, raw) in points.iter().zip(&raw_points) {
+        assert_eq!(point.x, raw.x as f64);
+        assert_eq!(point.y, raw.y as f64);
+        assert_eq!(point.z, raw.z as f64);
+        assert_eq!(point.rgb, raw.rgb as u32);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn write_converted_fields() -> Result<()> {
+    let schema = <ColorPoint as PcdSerialize>::write_spec();
+    let kinds = schema.iter().map(|def| (def.kind, def.count)).collect_vec();
+    assert_eq!(
+        kinds,
+        vec![
+            (ValueKind::I16, 2),
+            (ValueKind::F32, 1),
+            (ValueKind::U32, 1)
+        ]
+    );
+
+    let points = vec![
+        ColorPoint {
+            x: [1, -2],
+            rgb: [255, 128, 0],
+            rgba: [255, 0, 16, 128],
+        },
+        ColorPoint {
+            x: [300, 0],
+            rgb: [0, 0, 0],
+            rgba: [1, 2, 3, 4],
+        },
+    ];
+
+    for data_kind in [DataKind::Ascii, DataKind::Binary] {
+        let bytes = common::write_points(&points, data_kind, None)?;
+        let load_points: Vec<ColorPoint> = common::read_points(&bytes)?;
+        assert_eq!(load_points, points);
+    }
+
+    // PCL packs rgba as 0xAARRGGBB
+    assert_eq!(codec::rgba::encode(&[0x11, 0x22, 0x33, 0x44]), 0x44112233);
+    assert_eq!(
+        codec::rgb::decode(codec::PackedRgb(0x112233)),
+        [0x11, 0x22, 0x33]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn reject_out_of_range_conversions() -> Result<()> {
+    let is_cast_error = |error: anyhow::Error| {
+        matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::FieldCastError { name, .. }) if name == "x"
+        )
+    };
+
+    for data_kind in [DataKind::Ascii, DataKind::Binary] {
+        let point = ColorPoint {
+            x: [70000, 0],
+            rgb: [0, 0, 0],
+            rgba: [0, 0, 0, 0],
+        };
+        let mut output = Cursor::new(vec![]);
+        let mut writer = WriterInit {
+            data_kind,
+            ..Default::default()
+        }
+        .build_from_writer(&mut output)?;
+        assert!(is_cast_error(writer.push(&point).unwrap_err()));
+        writer.finish()?;
+
+        let point = ColorPoint {
+            x: [300, 0],
+            ..point
+        };
+        let mut output = Cursor::new(vec![]);
+        let mut writer = WriterInit {
+            data_kind,
+            ..Default::default()
+        }
+        .build_from_writer(&mut output)?;
+        writer.push(&point)?;
+        writer.finish()?;
+
+        let bytes = output.into_inner();
+        let mut reader = Reader::<NarrowPoint, _>::from_bytes(&bytes)?;
+        let error = reader.next().unwrap().unwrap_err();
+        assert!(error.downcast_ref::<Error>().is_some_and(
+            |error| matches!(error, Error::FieldCastError { name, .. } if name == "x")
+        ));
+    }
+
+    Ok(())
+}
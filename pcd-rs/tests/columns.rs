@@ -0,0 +1,84 @@
+#![cfg(feature = "derive")]
+
+mod common;
+
+use anyhow::Result;
+use itertools::Itertools as _;
+use pcd_rs::{DataKind, PcdColumns, PcdDeserialize, PcdSerialize, Reader, WriterInit};
+use std::io::Cursor;
+
+#[derive(Debug, Clone, PartialEq, PcdDeserialize, PcdSerialize, PcdColumns)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    #[pcd(with = "pcd_rs::codec::rgb")]
+    pub rgb: [u8; 3],
+}
+
+#[derive(Debug, Clone, PartialEq, PcdDeserialize, PcdColumns)]
+pub struct BinaryPoint {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub rgb: u32,
+}
+
+#[test]
+fn read_write_columns() -> Result<()> {
+    let points: Vec<Point> = Reader::open("test_files/ascii.pcd")?.try_collect()?;
+
+    let mut columns = PointColumns::new();
+    let count = Reader::<Point, _>::open("test_files/ascii.pcd")?.read_columns(&mut columns)?;
+    assert_eq!(count, points.len());
+    assert_eq!(columns.len(), points.len());
+    assert_eq!(columns.x.len(), points.len());
+    assert_eq!(columns.rgb[0], [64, 64, 112]);
+    for (index, point) in points.iter().enumerate() {
+        assert_eq!(columns.get(index).as_ref(), Some(point));
+    }
+    assert_eq!(columns.get(points.len()), None);
+
+    for data_kind in [DataKind::Ascii, DataKind::Binary] {
+        let mut output = Cursor::new(vec![]);
+        let mut writer = WriterInit {
+            width: columns.len() as u64,
+            height: 1,
+            data_kind,
+            schema: None,
+            ..Default::default()
+        }
+        .build_from_writer::<Point, _>(&mut output)?;
+        writer.push_columns(&columns)?;
+        writer.finish()?;
+
+        // Columns are written like the records
+        let bytes = output.into_inner();
+        assert_eq!(bytes, common::write_points(&points, data_kind, None)?);
+        let load_points: Vec<Point> = common::read_points(&bytes)?;
+        assert_eq!(load_points, points);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn read_valid_columns() -> Result<()> {
+    let points: Vec<BinaryPoint> = Reader::open("test_files/binary.pcd")?
+        .skip_invalid()?
+        .try_collect()?;
+
+    let mut columns = BinaryPointColumns::with_capacity(points.len());
+    columns.push(points[0].clone());
+    let mut reader = Reader::<BinaryPoint, _>::open("test_files/binary.pcd")?.skip_invalid()?;
+    let count = reader.read_columns(&mut columns)?;
+
+    assert_eq!(count, points.len());
+    assert_eq!(PcdColumns::len(&columns), points.len() + 1);
+    assert!(columns.x.iter().all(|x| x.is_finite()));
+    assert_eq!(columns.get(count), points.last().cloned());
+
+    // All points are read
+    assert_eq!(reader.read_columns(&mut columns)?, 0);
+    Ok(())
+}
@@ -0,0 +1,34 @@
+//! Helpers shared by the tests that write points and read them back.
+
+use anyhow::Result;
+use itertools::Itertools as _;
+use pcd_rs::{DataKind, PcdDeserialize, PcdSerialize, Reader, Schema, WriterInit};
+use std::io::Cursor;
+
+/// Writes the points as one row of PCD data in memory. The schema is
+/// derived from the record type if not given.
+pub fn write_points<T: PcdSerialize>(
+    points: &[T],
+    data_kind: DataKind,
+    schema: Option<Schema>,
+) -> Result<Vec<u8>> {
+    let mut output = Cursor::new(vec![]);
+    let mut writer = WriterInit {
+        width: points.len() as u64,
+        height: 1,
+        data_kind,
+        schema,
+        ..Default::default()
+    }
+    .build_from_writer(&mut output)?;
+    // The writer must be finished before a push error is returned
+    let result = writer.push_all(points);
+    writer.finish()?;
+    result?;
+    Ok(output.into_inner())
+}
+
+/// Reads all points from PCD data in memory.
+pub fn read_points<T: PcdDeserialize>(bytes: &[u8]) -> Result<Vec<T>> {
+    Reader::from_bytes(bytes)?.try_collect()
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "derive")]
+
+use anyhow::Result;
+use pcd_rs::{
+    pcl::PointXYZRGB, DynReader, PcdDeserialize, PcdSchema, PcdSerialize, Reader, StaticFieldDef,
+    ValueKind,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, pcd_rs::PcdValue)]
+#[repr(u16)]
+pub enum Label {
+    Ground = 0,
+    Object = 1,
+}
+
+#[derive(PcdSchema, PcdSerialize)]
+#[pcd(prefix = "p_")]
+pub struct Sample {
+    xyz: [f32; 3],
+    #[pcd(as = "u8")]
+    ring: u32,
+    label: Label,
+    #[pcd(with = "pcd_rs::codec::rgb")]
+    rgb: [u8; 3],
+    intensity: Option<f64>,
+    #[pcd(count = 4, rename = "hist")]
+    histogram: Vec<i16>,
+}
+
+#[derive(PcdDeserialize, PcdSerialize)]
+pub struct Histogram {
+    bins: Vec<u32>,
+}
+
+#[test]
+fn derive_const_schema() {
+    const FIELDS: &[StaticFieldDef] = <Sample as PcdSchema>::FIELDS;
+    const SIZE: usize = <Sample as PcdSchema>::RECORD_SIZE;
+
+    let expect = [
+        ("p_xyz", ValueKind::F32, 3),
+        ("p_ring", ValueKind::U8, 1),
+        ("p_label", ValueKind::U16, 1),
+        ("p_rgb", ValueKind::F32, 1),
+        ("p_intensity", ValueKind::F64, 1),
+        ("hist", ValueKind::I16, 4),
+    ];
+    let fields: Vec<_> = FIELDS
+        .iter()
+        .map(|def| (def.name, def.kind, def.count))
+        .collect();
+    assert_eq!(fields, expect);
+    assert_eq!(SIZE, 12 + 1 + 2 + 4 + 8 + 8);
+
+    let schema = Sample::schema();
+    assert_eq!(schema, <Sample as PcdSerialize>::write_spec());
+    assert_eq!(schema.record_size_bytes(), SIZE);
+    assert!(Sample::matches_schema(&schema));
+
+    // Record traits have the fixed fields unless Vec fields are uncounted
+    assert_eq!(<Sample as PcdSerialize>::WRITE_FIELDS, Some(FIELDS));
+    assert_eq!(<Histogram as PcdSerialize>::WRITE_FIELDS, None);
+    assert_eq!(<Histogram as PcdDeserialize>::READ_FIELDS, None);
+}
+
+#[test]
+fn match_file_schema() -> Result<()> {
+    let reader = DynReader::open("test_files/ascii.pcd")?;
+    assert!(PointXYZRGB::matches_schema(&reader.meta().field_defs));
+
+    // The rgb field is U32 in the file
+    let reader = DynReader::open("test_files/binary.pcd")?;
+    assert!(!PointXYZRGB::matches_schema(&reader.meta().field_defs));
+
+    // Readers check the schema by the fixed fields
+    assert_eq!(
+        <PointXYZRGB as PcdDeserialize>::READ_FIELDS,
+        Some(PointXYZRGB::FIELDS)
+    );
+    assert!(Reader::<PointXYZRGB, _>::open("test_files/ascii.pcd").is_ok());
+    assert!(Reader::<PointXYZRGB, _>::open("test_files/binary.pcd").is_err());
+    Ok(())
+}
@@ -0,0 +1,93 @@
+#![cfg(feature = "derive")]
+
+mod common;
+
+use anyhow::Result;
+use itertools::Itertools as _;
+use pcd_rs::{DataKind, DynReader, PcdDeserialize, PcdSerialize, Reader};
+
+#[derive(Debug, Clone, PartialEq, PcdDeserialize, PcdSerialize)]
+#[pcd(rename_all = "camelCase", prefix = "sensor_")]
+pub struct Reading {
+    pos_x: f32,
+    hit_count: u32,
+    #[pcd(rename = "id")]
+    reading_id: u16,
+}
+
+#[derive(Debug, PcdSerialize)]
+#[pcd(rename_all = "UPPERCASE")]
+pub struct UpperReading {
+    pos_x: f32,
+    #[pcd(rename = "Ring")]
+    ring: u16,
+}
+
+#[derive(Debug, PcdDeserialize)]
+#[pcd(deny_unknown = false)]
+pub struct Xz {
+    pub x: f32,
+    pub z: f32,
+}
+
+#[derive(Debug, PcdDeserialize)]
+pub struct StrictXz {
+    pub x: f32,
+    pub z: f32,
+}
+
+#[test]
+fn write_renamed_fields() -> Result<()> {
+    let points = (0..10)
+        .map(|idx| Reading {
+            pos_x: idx as f32 / 3.0,
+            hit_count: idx * 7,
+            reading_id: idx as u16,
+        })
+        .collect_vec();
+
+    for data_kind in [DataKind::Ascii, DataKind::Binary] {
+        let bytes = common::write_points(&points, data_kind, None)?;
+        let reader = DynReader::from_bytes(&bytes)?;
+        let names = reader
+            .meta()
+            .field_defs
+            .iter()
+            .map(|def| def.name.as_str())
+            .collect_vec();
+        assert_eq!(names, ["sensor_posX", "sensor_hitCount", "id"]);
+
+        let load_points: Vec<Reading> = common::read_points(&bytes)?;
+        assert_eq!(load_points, points);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn uppercase_keeps_underscores() {
+    let names = |schema: pcd_rs::Schema| schema.iter().map(|def| def.name.clone()).collect_vec();
+
+    // Underscores are kept by UPPERCASE
+    assert_eq!(names(UpperReading::write_spec()), ["POS_X", "Ring"]);
+}
+
+#[test]
+fn skip_unknown_fields() -> Result<()> {
+    for path in ["test_files/ascii.pcd", "test_files/binary.pcd"] {
+        let points: Vec<Xz> = Reader::open(path)?.try_collect()?;
+        let expect: Vec<_> = DynReader::open(path)?.try_collect()?;
+
+        assert_eq!(points.len(), expect.len());
+        for (point, expect) in points.iter().zip(expect) {
+            let [x, _, z] = expect.to_xyz::<f32>().unwrap();
+            assert!(point.x == x || point.x.is_nan() && x.is_nan());
+            assert!(point.z == z || point.z.is_nan() && z.is_nan());
+        }
+
+        // Unknown fields are rejected by default
+        assert!(Reader::<StrictXz, _>::open(path).is_err());
+    }
+
+    Ok(())
+}
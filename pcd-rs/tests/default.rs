@@ -0,0 +1,71 @@
+#![cfg(feature = "derive")]
+
+use anyhow::Result;
+use itertools::Itertools as _;
+use pcd_rs::{PcdDeserialize, Reader};
+
+#[derive(Debug, PcdDeserialize)]
+pub struct Point {
+    pub x: f32,
+    #[pcd(default = "one")]
+    pub weight: f64,
+    pub y: f32,
+    pub z: f32,
+    pub rgb: f32,
+    #[pcd(default)]
+    pub intensity: f32,
+    #[pcd(default)]
+    pub normal: Vec<f32>,
+}
+
+fn one() -> f64 {
+    1.0
+}
+
+#[derive(Debug, PcdDeserialize)]
+pub struct XyzRgb {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub rgb: f32,
+}
+
+#[derive(Debug, PcdDeserialize)]
+pub struct ColorPoint {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    #[pcd(default)]
+    pub rgb: u32,
+}
+
+#[test]
+fn read_missing_fields_with_defaults() -> Result<()> {
+    let expect: Vec<XyzRgb> = Reader::open("test_files/ascii.pcd")?.try_collect()?;
+    let points: Vec<Point> = Reader::open("test_files/ascii.pcd")?.try_collect()?;
+
+    assert_eq!(points.len(), expect.len());
+    for (point, expect) in points.iter().zip(expect) {
+        assert_eq!(point.x, expect.x);
+        assert_eq!(point.y, expect.y);
+        assert_eq!(point.z, expect.z);
+        assert_eq!(point.rgb, expect.rgb);
+        assert_eq!(point.weight, 1.0);
+        assert_eq!(point.intensity, 0.0);
+        assert!(point.normal.is_empty());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn read_present_default_field() -> Result<()> {
+    let points: Vec<ColorPoint> = Reader::open("test_files/binary.pcd")?.try_collect()?;
+    assert_eq!(points.len(), 28944);
+    assert!(points.iter().any(|point| point.rgb != 0));
+
+    // The rgb field is present but has another type
+    let result = Reader::<ColorPoint, _>::open("test_files/ascii.pcd");
+    assert!(result.is_err());
+    Ok(())
+}
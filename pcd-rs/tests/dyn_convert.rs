@@ -0,0 +1,163 @@
+#![cfg(feature = "derive")]
+
+use anyhow::Result;
+use itertools::Itertools as _;
+use pcd_rs::{
+    DynReader, DynRecord, Error, Field, PcdDeserialize, PcdSerialize, Reader, Schema, ValueKind,
+};
+
+#[derive(Debug, Clone, PartialEq, PcdDeserialize)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub rgb: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, PcdDeserialize)]
+pub struct ZxPoint {
+    pub z: f32,
+    pub x: f32,
+    #[pcd(default)]
+    pub intensity: f32,
+}
+
+#[derive(Debug, PcdDeserialize)]
+pub struct NormalPoint {
+    pub x: f32,
+    pub normal_x: f32,
+}
+
+#[derive(Debug, PcdDeserialize)]
+pub struct IntColorPoint {
+    pub rgb: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, PcdDeserialize, PcdSerialize)]
+pub struct Descriptor {
+    #[pcd(as = "u8")]
+    pub label: u32,
+    pub histogram: Vec<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq, PcdDeserialize, PcdSerialize)]
+pub struct ColorPoint {
+    #[pcd(with = "pcd_rs::codec::rgb")]
+    pub rgb: [u8; 3],
+    pub intensity: Option<f32>,
+    pub valid: bool,
+}
+
+#[derive(Debug, PcdDeserialize)]
+pub struct IgnoredColorPoint {
+    #[pcd(ignore)]
+    pub rgb: f32,
+    pub valid: bool,
+}
+
+#[derive(Debug, PcdSerialize)]
+pub struct FixedDescriptor {
+    #[pcd(count = 2)]
+    pub histogram: Vec<f32>,
+}
+
+#[test]
+fn convert_dyn_records() -> Result<()> {
+    let reader = DynReader::open("test_files/ascii.pcd")?;
+    let schema = reader.meta().field_defs.clone();
+    let records: Vec<DynRecord> = reader.try_collect()?;
+    let points: Vec<Point> = Reader::open("test_files/ascii.pcd")?.try_collect()?;
+
+    for (record, point) in records.iter().zip(&points) {
+        assert_eq!(Point::try_from((record, &schema))?, *point);
+
+        // Fields are mapped by name
+        let zx_point = ZxPoint::try_from((record, &schema))?;
+        assert_eq!((zx_point.x, zx_point.z), (point.x, point.z));
+        assert_eq!(zx_point.intensity, 0.0);
+    }
+
+    let record = &records[0];
+    let error = NormalPoint::try_from((record, &schema)).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<Error>(),
+        Some(Error::FieldNotFoundError { name }) if name == "normal_x"
+    ));
+    let error = IntColorPoint::try_from((record, &schema)).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<Error>(),
+        Some(Error::FieldKindMismatchError {
+            expect: ValueKind::U32,
+            found: ValueKind::F32,
+            ..
+        })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn convert_into_dyn_record() -> Result<()> {
+    let descriptor = Descriptor {
+        label: 7,
+        histogram: vec![0.5, 1.5, 2.5],
+    };
+
+    let record = DynRecord::try_from(descriptor.clone())?;
+    assert_eq!(
+        record,
+        DynRecord(vec![Field::U8(vec![7]), Field::F32(vec![0.5, 1.5, 2.5])])
+    );
+
+    let schema: Schema = [
+        ("label", ValueKind::U8, 1),
+        ("histogram", ValueKind::F32, 3),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(Descriptor::try_from((&record, &schema))?, descriptor);
+
+    // Vec fields with the count option must have the count
+    let fixed = FixedDescriptor {
+        histogram: vec![0.5, 1.5, 2.5],
+    };
+    let error = DynRecord::try_from(&fixed).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<Error>(),
+        Some(Error::FieldSizeMismatchError { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn convert_value_fields() -> Result<()> {
+    let point = ColorPoint {
+        rgb: [255, 128, 0],
+        intensity: None,
+        valid: true,
+    };
+
+    // Values are stored like in PCD data
+    let record = DynRecord::try_from(&point)?;
+    let [field_rgb, field_intensity, field_valid] = &record.0[..] else {
+        panic!("expect 3 fields");
+    };
+    assert_eq!(field_rgb.kind(), ValueKind::F32);
+    assert!(matches!(field_intensity, Field::F32(values) if values[0].is_nan()));
+    assert_eq!(*field_valid, Field::U8(vec![1]));
+
+    let schema: Schema = [
+        ("rgb", ValueKind::F32, 1),
+        ("intensity", ValueKind::F32, 1),
+        ("valid", ValueKind::U8, 1),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(ColorPoint::try_from((&record, &schema))?, point);
+
+    // Ignored fields are not looked up
+    let ignored = IgnoredColorPoint::try_from((&record, &schema))?;
+    assert_eq!(ignored.rgb, 0.0);
+    assert!(ignored.valid);
+    Ok(())
+}
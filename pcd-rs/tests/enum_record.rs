@@ -0,0 +1,112 @@
+#![cfg(feature = "derive")]
+
+use anyhow::Result;
+use itertools::Itertools as _;
+use pcd_rs::{DataKind, DynReader, DynRecord, PcdDeserialize, PcdSerialize, Reader, WriterInit};
+
+#[derive(Debug, Clone, PartialEq, PcdDeserialize, PcdSerialize)]
+pub struct Xyz {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, PcdDeserialize)]
+pub struct XyzRgb {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub rgb: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, PcdDeserialize)]
+pub struct XyzPackedRgb {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub rgb: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, PcdDeserialize)]
+pub enum AnyPoint {
+    Xyz(Xyz),
+    Rgb(XyzRgb),
+    PackedRgb(XyzPackedRgb),
+}
+
+#[derive(Debug, PcdSerialize)]
+pub struct Xy {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[test]
+fn read_enum_records() -> Result<()> {
+    let points: Vec<AnyPoint> = Reader::open("test_files/ascii.pcd")?.try_collect()?;
+    let expect: Vec<XyzRgb> = Reader::open("test_files/ascii.pcd")?.try_collect()?;
+    assert_eq!(points, expect.into_iter().map(AnyPoint::Rgb).collect_vec());
+
+    let mut reader = Reader::<AnyPoint, _>::open("test_files/binary.pcd")?;
+    let mut points = vec![];
+    reader.read_block(&mut points, 1000)?;
+    points.extend(reader.collect::<Result<Vec<_>>>()?);
+    assert_eq!(points.len(), 28944);
+    assert!(points
+        .iter()
+        .all(|point| matches!(point, AnyPoint::PackedRgb(_))));
+
+    // The first variant that matches is picked
+    let path = "test_files/dump_enum_xyz.pcd";
+    let mut writer = WriterInit {
+        width: 1,
+        height: 1,
+        data_kind: DataKind::Ascii,
+        schema: None,
+        ..Default::default()
+    }
+    .create(path)?;
+    let xyz = Xyz {
+        x: 1.0,
+        y: 2.0,
+        z: 3.0,
+    };
+    writer.push(&xyz)?;
+    writer.finish()?;
+    let points: Vec<AnyPoint> = Reader::open(path)?.try_collect()?;
+    assert_eq!(points, [AnyPoint::Xyz(xyz)]);
+    std::fs::remove_file(path)?;
+
+    // The spec of the enum is the first variant's
+    assert_eq!(AnyPoint::read_spec(), Xyz::read_spec());
+
+    // No variant matches
+    let path = "test_files/dump_enum_xy.pcd";
+    let mut writer = WriterInit {
+        width: 1,
+        height: 1,
+        data_kind: DataKind::Binary,
+        schema: None,
+        ..Default::default()
+    }
+    .create(path)?;
+    writer.push(&Xy { x: 1.0, y: 2.0 })?;
+    writer.finish()?;
+    assert!(Reader::<AnyPoint, _>::open(path).is_err());
+    std::fs::remove_file(path)?;
+
+    Ok(())
+}
+
+#[test]
+fn convert_dyn_record_to_enum() -> Result<()> {
+    let reader = DynReader::open("test_files/binary.pcd")?;
+    let schema = reader.meta().field_defs.clone();
+    let records: Vec<DynRecord> = reader.take(10).try_collect()?;
+
+    for record in &records {
+        // Xyz is picked since extra fields are allowed by name
+        let point = AnyPoint::try_from((record, &schema))?;
+        assert!(matches!(point, AnyPoint::Xyz(_)));
+    }
+    Ok(())
+}
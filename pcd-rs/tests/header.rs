@@ -0,0 +1,124 @@
+use anyhow::Result;
+use itertools::Itertools as _;
+use pcd_rs::{DynReader, DynRecord, Field, PcdMeta, Schema, ValueKind, WriterInit};
+use std::{fs, io::Cursor};
+
+fn rewrite(bytes: &[u8]) -> Result<Vec<u8>> {
+    let reader = DynReader::from_bytes(bytes)?;
+    let meta = reader.meta().clone();
+    let points: Vec<DynRecord> = reader.try_collect()?;
+
+    let mut output = Cursor::new(vec![]);
+    let mut writer = WriterInit::from_meta(&meta).build_from_writer(&mut output)?;
+    for point in &points {
+        writer.push(point)?;
+    }
+    writer.finish()?;
+
+    Ok(output.into_inner())
+}
+
+#[test]
+fn rewrite_binary_exactly() -> Result<()> {
+    let bytes = fs::read("test_files/binary.pcd")?;
+    let (meta, data_offset) = PcdMeta::read_from(bytes.as_slice())?;
+    let data_end = data_offset as usize + meta.num_points as usize * 16;
+
+    let output = rewrite(&bytes)?;
+    assert_eq!(output, bytes[..data_end]);
+
+    Ok(())
+}
+
+#[test]
+fn rewrite_ascii_header_exactly() -> Result<()> {
+    let bytes = fs::read("test_files/ascii.pcd")?;
+    let (_, data_offset) = PcdMeta::read_from(bytes.as_slice())?;
+    let data_offset = data_offset as usize;
+
+    let output = rewrite(&bytes)?;
+    assert_eq!(output[..data_offset], bytes[..data_offset]);
+
+    Ok(())
+}
+
+#[test]
+fn write_header_round_trip() -> Result<()> {
+    let (meta, _) = PcdMeta::from_path("test_files/ascii.pcd")?;
+
+    let mut header = vec![];
+    meta.write_header(&mut header)?;
+    let (new_meta, data_offset) = PcdMeta::read_from(header.as_slice())?;
+
+    assert_eq!(new_meta, meta);
+    assert_eq!(data_offset as usize, header.len());
+
+    Ok(())
+}
+
+#[test]
+fn custom_comments_round_trip() -> Result<()> {
+    let (meta, _) = PcdMeta::from_path("test_files/ascii.pcd")?;
+    assert_eq!(meta.comments, [" .PCD v.7 - Point Cloud Data file format"]);
+
+    let comments = vec![
+        " calibration: rig-42".to_string(),
+        "timestamp:\t2020-01-01T00:00:00Z  ".to_string(),
+    ];
+    let mut output = Cursor::new(vec![]);
+    let writer = WriterInit {
+        comments: comments.clone(),
+        width: 0,
+        num_points: None,
+        ..WriterInit::from_meta(&meta)
+    }
+    .build_from_writer::<DynRecord, _>(&mut output)?;
+    writer.finish()?;
+
+    let (new_meta, _) = PcdMeta::read_from(output.into_inner().as_slice())?;
+    assert_eq!(new_meta.comments, comments);
+
+    Ok(())
+}
+
+#[test]
+fn inline_comments_move_to_leading_lines() -> Result<()> {
+    let data = "VERSION .7\nFIELDS x # position\nSIZE 4\nTYPE F\nCOUNT 1\n\
+                WIDTH 1\nHEIGHT 1\nVIEWPOINT 0 0 0 1 0 0 0\nPOINTS 1\nDATA ascii\n1\n";
+    let (meta, _) = PcdMeta::read_from(data.as_bytes())?;
+    assert_eq!(meta.comments, [" position"]);
+
+    let header = String::from_utf8(rewrite(data.as_bytes())?)?;
+    assert!(header.starts_with("# position\nVERSION .7\nFIELDS x\n"));
+    Ok(())
+}
+
+#[test]
+fn fill_width_of_unorganized_cloud() -> Result<()> {
+    let mut output = Cursor::new(vec![]);
+    let mut writer = WriterInit {
+        schema: Some(Schema::from_iter([("x", ValueKind::F32, 1)])),
+        ..Default::default()
+    }
+    .build_from_writer(&mut output)?;
+    for index in 0..3 {
+        writer.push(&DynRecord(vec![Field::F32(vec![index as f32])]))?;
+    }
+    writer.finish()?;
+
+    let (meta, _) = PcdMeta::read_from(output.into_inner().as_slice())?;
+    assert_eq!((meta.width, meta.height, meta.num_points), (3, 1, 3));
+
+    // A given WIDTH is kept and must match the number of points
+    let mut output = Cursor::new(vec![]);
+    let mut writer = WriterInit {
+        width: 3,
+        schema: Some(Schema::from_iter([("x", ValueKind::F32, 1)])),
+        ..Default::default()
+    }
+    .build_from_writer(&mut output)?;
+    writer.push(&DynRecord(vec![Field::F32(vec![0.0])]))?;
+    assert!(writer.finish().is_err());
+
+    Ok(())
+}
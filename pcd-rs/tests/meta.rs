@@ -0,0 +1,65 @@
+use anyhow::Result;
+use pcd_rs::{DataKind, Error, PcdMeta};
+use std::fs;
+
+#[test]
+fn probe_meta() -> Result<()> {
+    let (meta, data_offset) = PcdMeta::from_path("test_files/binary.pcd")?;
+    assert_eq!(meta.num_points, 28944);
+    assert_eq!(meta.data, DataKind::Binary);
+
+    let bytes = fs::read("test_files/binary.pcd")?;
+    assert!(bytes[..data_offset as usize].ends_with(b"DATA binary\n"));
+
+    let (ascii_meta, _) = PcdMeta::read_from(&fs::read("test_files/ascii.pcd")?[..])?;
+    assert_eq!(ascii_meta.num_points, 213);
+
+    Ok(())
+}
+
+#[test]
+fn probe_truncated_binary() -> Result<()> {
+    let path = "test_files/truncated_binary.pcd";
+    let bytes = fs::read("test_files/binary.pcd")?;
+    fs::write(path, &bytes[..(bytes.len() - 10000)])?;
+
+    let result = PcdMeta::from_path(path);
+    fs::remove_file(path)?;
+
+    let error = result.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<Error>(),
+        Some(Error::TruncatedDataError { expect: 463104, .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn probe_overflowing_points() -> Result<()> {
+    let path = "test_files/overflowing_points.pcd";
+    let header = "\
+VERSION 0.7
+FIELDS x
+SIZE 4
+TYPE F
+COUNT 1
+WIDTH 1
+HEIGHT 1
+VIEWPOINT 0 0 0 1 0 0 0
+POINTS 18446744073709551615
+DATA binary
+";
+    fs::write(path, header)?;
+
+    let result = PcdMeta::from_path(path);
+    fs::remove_file(path)?;
+
+    let error = result.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<Error>(),
+        Some(Error::ParseError { .. })
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use anyhow::Result;
+use itertools::Itertools as _;
+use pcd_rs::{ros::PointCloud2, DynReader, DynRecord, Field, PcdMeta, WriterInit};
+use std::io::Cursor;
+
+const ORGANIZED_ASCII: &str = "\
+VERSION .7
+FIELDS x y z label
+SIZE 4 4 4 1
+TYPE F F F U
+COUNT 1 1 1 1
+WIDTH 2
+HEIGHT 2
+VIEWPOINT 0 0 0 1 0 0 0
+POINTS 4
+DATA ascii
+1 2 3 4
+nan nan nan 5
+-nan 0 0 6
+7 8 9 7
+";
+
+#[test]
+fn read_write_nan_ascii() -> Result<()> {
+    let reader = DynReader::from_bytes(ORGANIZED_ASCII.as_bytes())?;
+    let meta = reader.meta().clone();
+    let points: Vec<DynRecord> = reader.try_collect()?;
+    assert_eq!(points.len(), 4);
+    assert!(points[1].to_xyz::<f32>().unwrap()[0].is_nan());
+
+    let valid: Vec<DynRecord> = DynReader::from_bytes(ORGANIZED_ASCII.as_bytes())?
+        .skip_invalid()?
+        .try_collect()?;
+    assert_eq!(valid.len(), 2);
+    assert_eq!(valid[1].0[3], Field::U8(vec![7]));
+
+    assert!(!DynReader::from_bytes(ORGANIZED_ASCII.as_bytes())?.is_dense()?);
+    assert!(DynReader::from_bytes(ORGANIZED_ASCII.replace("nan", "0").as_bytes())?.is_dense()?);
+
+    let mut output = Cursor::new(vec![]);
+    let mut writer = WriterInit::from_meta(&meta).build_from_writer(&mut output)?;
+    writer.push_all(&points)?;
+    writer.finish()?;
+
+    let text = String::from_utf8(output.into_inner())?;
+    assert!(text.ends_with("1 2 3 4\nnan nan nan 5\nnan 0 0 6\n7 8 9 7\n"));
+
+    Ok(())
+}
+
+#[test]
+fn skip_invalid_binary() -> Result<()> {
+    let reader = DynReader::open("test_files/binary.pcd")?;
+    let meta = reader.meta().clone();
+    let mut points: Vec<DynRecord> = reader.try_collect()?;
+    assert!(DynReader::open("test_files/binary.pcd")?.is_dense()?);
+
+    // Invalidate every third point
+    for point in points.iter_mut().step_by(3) {
+        point.0[1] = Field::F32(vec![f32::NAN]);
+    }
+    let mut output = Cursor::new(vec![]);
+    let mut writer = WriterInit::from_meta(&meta).build_from_writer(&mut output)?;
+    writer.push_all(&points)?;
+    writer.finish()?;
+    let bytes = output.into_inner();
+
+    let mut reader = DynReader::from_bytes(&bytes)?.skip_invalid()?;
+    let mut valid = vec![];
+    while reader.read_block(&mut valid, 1000)? > 0 {}
+    assert_eq!(valid.len(), points.len() - points.len().div_ceil(3));
+    assert_eq!(valid[0], points[1]);
+
+    assert!(!DynReader::from_bytes(&bytes)?.is_dense()?);
+
+    let (meta, data_offset) = PcdMeta::read_from(bytes.as_slice())?;
+    let msg = PointCloud2::from_pcd(&meta, &bytes[(data_offset as usize)..])?;
+    assert!(!msg.is_dense);
+
+    Ok(())
+}
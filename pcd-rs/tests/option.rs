@@ -0,0 +1,145 @@
+#![cfg(feature = "derive")]
+
+mod common;
+
+use anyhow::Result;
+use itertools::Itertools as _;
+use pcd_rs::{DataKind, Error, PcdDeserialize, PcdSerialize, Reader, WriterInit};
+use std::io::Cursor;
+
+#[derive(Debug, Clone, PartialEq, PcdDeserialize, PcdSerialize)]
+pub struct Point {
+    x: f32,
+    intensity: Option<f32>,
+    #[pcd(none = 0xFFFF)]
+    ring: Option<u16>,
+    #[pcd(none = -1)]
+    label: std::option::Option<i32>,
+}
+
+#[derive(Debug, PcdDeserialize)]
+pub struct RawPoint {
+    #[pcd(ignore)]
+    _x: f32,
+    intensity: f32,
+    ring: u16,
+    label: i32,
+}
+
+#[test]
+fn write_option_fields() -> Result<()> {
+    let points = vec![
+        Point {
+            x: 1.0,
+            intensity: Some(0.5),
+            ring: Some(3),
+            label: Some(7),
+        },
+        Point {
+            x: 2.0,
+            intensity: None,
+            ring: None,
+            label: None,
+        },
+    ];
+
+    for data_kind in [DataKind::Ascii, DataKind::Binary] {
+        let bytes = common::write_points(&points, data_kind, None)?;
+        let load_points: Vec<Point> = common::read_points(&bytes)?;
+        assert_eq!(load_points, points);
+
+        // None is encoded as NaN or the sentinel value
+        let raw_points: Vec<RawPoint> = common::read_points(&bytes)?;
+        assert!(raw_points[1].intensity.is_nan());
+        assert_eq!(raw_points[1].ring, 0xFFFF);
+        assert_eq!(raw_points[1].label, -1);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn read_nan_as_none() -> Result<()> {
+    #[derive(Debug, PcdDeserialize)]
+    pub struct XyzPoint {
+        x: Option<f32>,
+        y: Option<f32>,
+        z: Option<f32>,
+    }
+
+    let data = b"\
+VERSION .7
+FIELDS x y z
+SIZE 4 4 4
+TYPE F F F
+COUNT 1 1 1
+WIDTH 2
+HEIGHT 1
+VIEWPOINT 0 0 0 1 0 0 0
+POINTS 2
+DATA ascii
+1 2 3
+nan nan nan
+";
+
+    let points: Vec<XyzPoint> = Reader::from_bytes(data)?.try_collect()?;
+    let values = points
+        .iter()
+        .map(|point| [point.x, point.y, point.z])
+        .collect_vec();
+    assert_eq!(
+        values,
+        vec![[Some(1.0), Some(2.0), Some(3.0)], [None, None, None]]
+    );
+    Ok(())
+}
+
+#[test]
+fn reject_some_none_value() -> Result<()> {
+    let valid = Point {
+        x: 0.0,
+        intensity: Some(1.0),
+        ring: Some(0),
+        label: Some(0),
+    };
+    let points = [
+        Point {
+            intensity: Some(f32::NAN),
+            ..valid.clone()
+        },
+        Point {
+            ring: Some(0xFFFF),
+            ..valid.clone()
+        },
+        Point {
+            label: Some(-1),
+            ..valid.clone()
+        },
+    ];
+
+    for data_kind in [DataKind::Ascii, DataKind::Binary] {
+        let mut output = Cursor::new(vec![]);
+        let mut writer = WriterInit {
+            data_kind,
+            ..Default::default()
+        }
+        .build_from_writer(&mut output)?;
+
+        for (point, expect) in points.iter().zip(["intensity", "ring", "label"]) {
+            let error = writer.push(point).unwrap_err();
+            assert!(matches!(
+                error.downcast_ref::<Error>(),
+                Some(Error::OptionSentinelError { name }) if name == expect
+            ));
+        }
+        writer.push(&valid)?;
+        writer.finish()?;
+
+        // Rejected points leave no partial records
+        let load_points: Vec<Point> = common::read_points(output.get_ref())?;
+        assert_eq!(load_points.len(), 1);
+        assert_eq!(load_points[0], valid);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use anyhow::Result;
+use itertools::Itertools as _;
+use pcd_rs::{DataKind, DynReader, DynRecord, Field, Reader, Schema, ValueKind, WriterInit};
+use std::io::Cursor;
+
+fn padded_binary_pcd() -> Vec<u8> {
+    let mut bytes = b"\
+VERSION 0.7
+FIELDS x _ y _
+SIZE 4 1 2 1
+TYPE F U U U
+COUNT 1 2 1 2
+WIDTH 2
+HEIGHT 1
+VIEWPOINT 0 0 0 1 0 0 0
+POINTS 2
+DATA binary
+"
+    .to_vec();
+
+    for (x, y) in [(1.5f32, 3u16), (-4.0, 9)] {
+        bytes.extend(x.to_le_bytes());
+        bytes.extend([0; 2]);
+        bytes.extend(y.to_le_bytes());
+        bytes.extend([0; 2]);
+    }
+
+    bytes
+}
+
+#[test]
+fn read_padded_binary() -> Result<()> {
+    let bytes = padded_binary_pcd();
+    let reader = DynReader::from_bytes(&bytes)?;
+
+    let schema = &reader.meta().field_defs;
+    assert_eq!(schema.len(), 4);
+    assert!(schema[1].padding && schema[3].padding);
+    assert_eq!(schema[2].offset, 6);
+
+    let record_spec = schema.without_padding();
+    record_spec.validate()?;
+    assert_eq!(record_spec[1].offset, 4);
+
+    let points: Vec<DynRecord> = reader.try_collect()?;
+    assert_eq!(
+        points,
+        vec![
+            DynRecord(vec![Field::F32(vec![1.5]), Field::U16(vec![3])]),
+            DynRecord(vec![Field::F32(vec![-4.0]), Field::U16(vec![9])]),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn write_padded_binary() -> Result<()> {
+    let bytes = padded_binary_pcd();
+    let reader = DynReader::from_bytes(&bytes)?;
+    let schema = reader.meta().field_defs.clone();
+    let points: Vec<DynRecord> = reader.try_collect()?;
+
+    let mut output = Cursor::new(vec![]);
+    let mut writer = WriterInit {
+        width: 2,
+        height: 1,
+        viewpoint: Default::default(),
+        data_kind: DataKind::Binary,
+        schema: Some(schema.clone()),
+        ..Default::default()
+    }
+    .build_from_writer(&mut output)?;
+
+    for point in &points {
+        writer.push(point)?;
+    }
+
+    writer.finish()?;
+
+    let output = output.into_inner();
+    let data_begin = output.len() - 20;
+    assert_eq!(output[data_begin..], bytes[(bytes.len() - 20)..]);
+
+    let reader: Reader<DynRecord, _> = Reader::from_bytes(&output)?;
+    assert_eq!(reader.meta().field_defs, schema);
+
+    Ok(())
+}
+
+#[test]
+fn schema_from_offsets() -> Result<()> {
+    let schema = Schema::from_offsets(
+        [("x", ValueKind::F32, 1, 0), ("y", ValueKind::U16, 1, 8)],
+        12,
+    )?;
+    let names: Vec<_> = schema.iter().map(|def| def.name.as_str()).collect();
+    assert_eq!(names, ["x", "_", "y", "_"]);
+    assert_eq!(schema[1].count, 4);
+    assert_eq!(schema[3].count, 2);
+
+    assert!(Schema::from_offsets(
+        [("x", ValueKind::F32, 1, 0), ("y", ValueKind::U16, 1, 2)],
+        12
+    )
+    .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn read_padded_ascii() -> Result<()> {
+    let bytes = b"\
+VERSION 0.7
+FIELDS x _ y
+SIZE 4 1 2
+TYPE F U U
+COUNT 1 2 1
+WIDTH 1
+HEIGHT 1
+VIEWPOINT 0 0 0 1 0 0 0
+POINTS 1
+DATA ascii
+1.5 0 0 3
+";
+    let reader = DynReader::from_bytes(bytes)?;
+    let points: Vec<DynRecord> = reader.try_collect()?;
+    assert_eq!(
+        points,
+        vec![DynRecord(vec![Field::F32(vec![1.5]), Field::U16(vec![3])])]
+    );
+    Ok(())
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "derive")]
+
+mod common;
+
+use anyhow::Result;
+use itertools::Itertools as _;
+use pcd_rs::{
+    pcl::{PointXYZRGB, PointXYZRGBNormal},
+    DataKind, PcdSerialize, Reader, ValueKind,
+};
+
+#[test]
+fn read_pcl_ascii_colors() -> Result<()> {
+    let points: Vec<PointXYZRGB> = Reader::open("test_files/ascii.pcd")?.try_collect()?;
+    assert_eq!(points.len(), 213);
+    // PCL writes packed colors like 0x404070 as 4.2108e+06 in Ascii data
+    let colors = points.iter().map(|point| point.rgb).unique().collect_vec();
+    assert_eq!(colors, vec![[64, 64, 112], [73, 93, 64]]);
+    Ok(())
+}
+
+#[test]
+fn write_pcl_point_normals() -> Result<()> {
+    let schema = PointXYZRGBNormal::write_spec();
+    let fields = schema
+        .iter()
+        .map(|def| (def.name.as_str(), def.kind, def.count))
+        .collect_vec();
+    assert_eq!(
+        fields,
+        vec![
+            ("x", ValueKind::F32, 1),
+            ("y", ValueKind::F32, 1),
+            ("z", ValueKind::F32, 1),
+            ("rgb", ValueKind::F32, 1),
+            ("normal_x", ValueKind::F32, 1),
+            ("normal_y", ValueKind::F32, 1),
+            ("normal_z", ValueKind::F32, 1),
+            ("curvature", ValueKind::F32, 1),
+        ]
+    );
+
+    let points = vec![
+        PointXYZRGBNormal {
+            x: 1.0,
+            rgb: [255, 255, 255],
+            normal_z: 1.0,
+            ..Default::default()
+        },
+        PointXYZRGBNormal {
+            y: -2.5,
+            rgb: [1, 2, 3],
+            normal_x: 1.0,
+            curvature: 0.25,
+            ..Default::default()
+        },
+    ];
+
+    for data_kind in [DataKind::Ascii, DataKind::Binary] {
+        let bytes = common::write_points(&points, data_kind, None)?;
+        let load_points: Vec<PointXYZRGBNormal> = common::read_points(&bytes)?;
+        assert_eq!(load_points, points);
+
+        if data_kind == DataKind::Ascii {
+            // Colors are integers like PCL writes
+            let text = String::from_utf8(bytes)?;
+            assert!(text.lines().any(|line| line == "1 0 0 16777215 0 0 1 0"));
+        }
+    }
+
+    Ok(())
+}
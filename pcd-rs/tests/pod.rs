@@ -0,0 +1,63 @@
+#![cfg(feature = "derive")]
+
+use anyhow::Result;
+use itertools::Itertools as _;
+use pcd_rs::{PcdDeserialize, PcdSerialize, Reader, WriterInit};
+use std::{fs, io::Cursor};
+
+#[derive(Debug, Clone, Copy, PartialEq, PcdDeserialize, PcdSerialize)]
+#[repr(C)]
+#[pcd(pod)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub rgb: u32,
+}
+
+#[test]
+fn read_pod_blocks() -> Result<()> {
+    let expect: Vec<Point> = Reader::open("test_files/binary.pcd")?.try_collect()?;
+
+    let bytes = fs::read("test_files/binary.pcd")?;
+    let mut reader = Reader::from_bytes(&bytes)?;
+    let mut points: Vec<Point> = vec![];
+    while reader.read_block(&mut points, 1000)? > 0 {}
+
+    assert_eq!(points.len(), 28944);
+    // Compare bits so that NaN values are equal
+    let to_bits = |p: &Point| [p.x.to_bits(), p.y.to_bits(), p.z.to_bits(), p.rgb];
+    assert_eq!(
+        points.iter().map(to_bits).collect_vec(),
+        expect.iter().map(to_bits).collect_vec()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn write_pod_blocks() -> Result<()> {
+    let points: Vec<_> = (0..1000)
+        .map(|index| Point {
+            x: index as f32,
+            y: 0.5,
+            z: -(index as f32),
+            rgb: index,
+        })
+        .collect();
+
+    let mut output = Cursor::new(vec![]);
+    let mut writer = WriterInit::default().build_from_writer(&mut output)?;
+    writer.push_all(&points)?;
+    writer.finish()?;
+
+    let bytes = output.into_inner();
+    let mut reader = Reader::<Point, _>::from_bytes(&bytes)?;
+    let mut load_points = vec![];
+    assert_eq!(reader.read_block(&mut load_points, 600)?, 600);
+    assert_eq!(reader.read_block(&mut load_points, 600)?, 400);
+    assert_eq!(reader.read_block(&mut load_points, 600)?, 0);
+    assert_eq!(load_points, points);
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use anyhow::Result;
+use pcd_rs::{
+    ros::{PointCloud2, PointField},
+    DynRecord, Reader, ValueKind,
+};
+
+#[test]
+fn binary_to_point_cloud2() -> Result<()> {
+    let bytes = std::fs::read("test_files/binary.pcd")?;
+    let reader: Reader<DynRecord, _> = Reader::from_bytes(&bytes)?;
+    let meta = reader.meta().clone();
+
+    let header = b"DATA binary\n";
+    let data_begin = bytes
+        .windows(header.len())
+        .position(|window| window == header)
+        .unwrap()
+        + header.len();
+    let data = &bytes[data_begin..(data_begin + 16 * meta.num_points as usize)];
+
+    let msg = PointCloud2::from_pcd(&meta, data)?;
+    assert_eq!(msg.width, 1809);
+    assert_eq!(msg.height, 16);
+    assert_eq!(msg.point_step, 16);
+    assert_eq!(msg.row_step, 16 * 1809);
+    assert_eq!(msg.fields[3].datatype, PointField::UINT32);
+
+    let (new_meta, new_data) = msg.to_pcd()?;
+    assert_eq!(new_meta.field_defs, meta.field_defs);
+    assert_eq!(new_data, data);
+
+    Ok(())
+}
+
+#[test]
+fn big_endian_point_cloud2_to_pcd() -> Result<()> {
+    let fields = vec![
+        PointField {
+            name: "intensity".into(),
+            offset: 8,
+            datatype: PointField::UINT16,
+            count: 1,
+        },
+        PointField {
+            name: "x".into(),
+            offset: 0,
+            datatype: PointField::FLOAT32,
+            count: 1,
+        },
+    ];
+
+    let data: Vec<u8> = [(1.5f32, 7u16), (-2.0, 300)]
+        .into_iter()
+        .flat_map(|(x, intensity)| {
+            let mut point = vec![0; 12];
+            point[0..4].copy_from_slice(&x.to_be_bytes());
+            point[8..10].copy_from_slice(&intensity.to_be_bytes());
+            point
+        })
+        .collect();
+
+    let msg = PointCloud2 {
+        height: 1,
+        width: 2,
+        fields,
+        is_bigendian: true,
+        point_step: 12,
+        row_step: 24,
+        data,
+        is_dense: true,
+    };
+
+    let (meta, data) = msg.to_pcd()?;
+    assert_eq!(meta.num_points, 2);
+    assert_eq!(data.len(), 24);
+
+    let names: Vec<_> = meta
+        .field_defs
+        .iter()
+        .map(|def| def.name.as_str())
+        .collect();
+    assert_eq!(names, ["x", "_", "intensity", "_"]);
+    assert!(meta.field_defs[1].padding);
+    assert_eq!(meta.field_defs[2].kind, ValueKind::U16);
+    assert_eq!(meta.field_defs[2].offset, 8);
+
+    let points: Vec<_> = data
+        .chunks_exact(12)
+        .map(|point| {
+            let x = f32::from_le_bytes(point[0..4].try_into().unwrap());
+            let intensity = u16::from_le_bytes(point[8..10].try_into().unwrap());
+            (x, intensity)
+        })
+        .collect();
+    assert_eq!(points, [(1.5, 7), (-2.0, 300)]);
+
+    Ok(())
+}
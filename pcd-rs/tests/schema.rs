@@ -0,0 +1,75 @@
+use anyhow::Result;
+use pcd_rs::{DynReader, Error, Schema, TypeKind, ValueKind};
+
+#[test]
+fn query_schema() -> Result<()> {
+    let reader = DynReader::open("test_files/binary.pcd")?;
+    let schema = &reader.meta().field_defs;
+
+    assert_eq!(schema.index_of("z"), Some(2));
+    assert_eq!(schema.offset_of("rgb"), Some(12));
+    assert_eq!(
+        schema.field("rgb").map(|def| def.kind),
+        Some(ValueKind::U32)
+    );
+    assert_eq!(schema.field("intensity"), None);
+    assert_eq!(schema.record_size_bytes(), 16);
+
+    assert_eq!(ValueKind::F64.size(), 8);
+    assert_eq!(ValueKind::I16.type_kind(), TypeKind::I);
+    assert_eq!(
+        ValueKind::from_type_size(TypeKind::U, 2),
+        Some(ValueKind::U16)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn build_invalid_schema() {
+    let error = Schema::builder()
+        .field("x", ValueKind::F32, 1)
+        .field("x", ValueKind::F32, 1)
+        .build()
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<Error>(),
+        Some(Error::DuplicateFieldError { name }) if name == "x"
+    ));
+
+    let error = Schema::builder()
+        .field("x", ValueKind::F32, 0)
+        .build()
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<Error>(),
+        Some(Error::ZeroCountError { .. })
+    ));
+
+    let error = Schema::builder()
+        .field("x", ValueKind::F64, 1)
+        .field_at("y", ValueKind::F32, 1, 4)
+        .build()
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<Error>(),
+        Some(Error::FieldOffsetError {
+            offset: 4,
+            end: 8,
+            ..
+        })
+    ));
+
+    let error = Schema::builder()
+        .field("x", ValueKind::F64, 1)
+        .record_size(4)
+        .build()
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<Error>(),
+        Some(Error::RecordSizeError {
+            expect: 8,
+            found: 4
+        })
+    ));
+}
@@ -0,0 +1,147 @@
+use anyhow::Result;
+use pcd_rs::{
+    text::{FromToken, Tokens},
+    DynRecord, Error, Field, PcdDeserialize, Schema, ValueKind,
+};
+use std::io::BufReader;
+
+#[test]
+fn parse_tokens_like_std() {
+    let floats = [
+        "0",
+        "-0",
+        "1.5",
+        "+2.25",
+        ".5",
+        "3.",
+        "1e10",
+        "-1.5E-3",
+        "0.1",
+        "123456789.123456789",
+        "3.4028235e38",
+        "1e-45",
+        "2.2250738585072014e-308",
+        "nan",
+        "inf",
+        "-inf",
+        "1e400",
+        "00012.5000",
+        "9007199254740993",
+    ];
+    for token in floats {
+        let f32_value = f32::from_token(token.as_bytes());
+        let f64_value = f64::from_token(token.as_bytes());
+        assert_eq!(
+            f32_value.map(f32::to_bits),
+            token.parse::<f32>().ok().map(f32::to_bits),
+            "{}",
+            token
+        );
+        assert_eq!(
+            f64_value.map(f64::to_bits),
+            token.parse::<f64>().ok().map(f64::to_bits),
+            "{}",
+            token
+        );
+    }
+
+    // Compare decimals generated from a simple linear congruential generator
+    let mut state = 12345u64;
+    for _ in 0..10000 {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let token = format!("{}.{}e-{}", state >> 40, state % 100000, state % 12);
+        assert_eq!(
+            f32::from_token(token.as_bytes()).map(f32::to_bits),
+            token.parse::<f32>().ok().map(f32::to_bits)
+        );
+        assert_eq!(
+            f64::from_token(token.as_bytes()).map(f64::to_bits),
+            token.parse::<f64>().ok().map(f64::to_bits)
+        );
+    }
+
+    let ints = [
+        "0",
+        "-0",
+        "+7",
+        "255",
+        "256",
+        "-128",
+        "-129",
+        "1.0",
+        "",
+        "-",
+        "99999999999999999999",
+    ];
+    for token in ints {
+        assert_eq!(
+            u8::from_token(token.as_bytes()),
+            token.parse().ok(),
+            "{}",
+            token
+        );
+        assert_eq!(
+            i8::from_token(token.as_bytes()),
+            token.parse().ok(),
+            "{}",
+            token
+        );
+        assert_eq!(
+            u32::from_token(token.as_bytes()),
+            token.parse().ok(),
+            "{}",
+            token
+        );
+        assert_eq!(
+            i32::from_token(token.as_bytes()),
+            token.parse().ok(),
+            "{}",
+            token
+        );
+    }
+
+    let tokens: Vec<_> = Tokens::new(b" \t1  2\r").collect();
+    assert_eq!(tokens, [b"1", b"2"]);
+}
+
+#[test]
+fn read_lines_across_buffer() -> Result<()> {
+    let schema = Schema::from_iter([("x", ValueKind::F32, 2), ("label", ValueKind::U16, 1)]);
+    let data = "1.25 -3 7\n0.5 2e2 65535\n4 5 6";
+    let mut reader = BufReader::with_capacity(4, data.as_bytes());
+
+    let mut records = vec![];
+    for _ in 0..3 {
+        records.push(DynRecord::read_line(&mut reader, &schema)?);
+    }
+    assert_eq!(
+        records[1],
+        DynRecord(vec![Field::F32(vec![0.5, 200.0]), Field::U16(vec![65535])])
+    );
+    assert_eq!(
+        records[2],
+        DynRecord(vec![Field::F32(vec![4.0, 5.0]), Field::U16(vec![6])])
+    );
+
+    let error = DynRecord::read_line(&mut "1 2\n".as_bytes(), &schema).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<Error>(),
+        Some(Error::TextTokenMismatchError {
+            expect: 3,
+            found: 2
+        })
+    ));
+
+    let error = DynRecord::read_line(&mut "1 2 -1\n".as_bytes(), &schema).unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<Error>(),
+        Some(Error::InvalidTokenError {
+            kind: ValueKind::U16,
+            ..
+        })
+    ));
+
+    Ok(())
+}
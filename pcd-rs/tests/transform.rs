@@ -0,0 +1,72 @@
+use anyhow::Result;
+use itertools::Itertools as _;
+use pcd_rs::{
+    transform::SchemaMap, DataKind, DynReader, DynRecord, Field, Reader, ValueKind, WriterInit,
+};
+use std::io::Cursor;
+
+#[test]
+fn project_fields() -> Result<()> {
+    let reader = DynReader::open("test_files/ascii.pcd")?.project(&["z", "x"])?;
+
+    let names: Vec<_> = reader
+        .meta()
+        .field_defs
+        .iter()
+        .map(|def| &def.name)
+        .collect();
+    assert_eq!(names, ["z", "x"]);
+
+    let points: Vec<DynRecord> = reader.try_collect()?;
+    assert_eq!(points.len(), 213);
+    assert_eq!(
+        points[0],
+        DynRecord(vec![Field::F32(vec![0.0]), Field::F32(vec![0.93773])])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn map_schema_into_writer() -> Result<()> {
+    let map = SchemaMap::new()
+        .rename_cast("rgb", "color", ValueKind::U32)
+        .cast("y", ValueKind::F64);
+    let reader = DynReader::open("test_files/ascii.pcd")?.map_schema(map)?;
+    let meta = reader.meta().clone();
+
+    let mut output = Cursor::new(vec![]);
+    let mut writer = WriterInit {
+        data_kind: DataKind::Binary,
+        ..WriterInit::from_meta(&meta)
+    }
+    .build_from_writer(&mut output)?;
+
+    for point in reader {
+        writer.push(&point?)?;
+    }
+    writer.finish()?;
+
+    let output = output.into_inner();
+    let reader: Reader<DynRecord, _> = Reader::from_bytes(&output)?;
+    assert_eq!(reader.meta().field_defs, meta.field_defs);
+
+    let points: Vec<DynRecord> = reader.try_collect()?;
+    assert_eq!(
+        points[0],
+        DynRecord(vec![
+            Field::U32(vec![4210800]),
+            Field::F64(vec![0.33763f32 as f64])
+        ])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn map_unknown_field() {
+    let result = DynReader::open("test_files/ascii.pcd")
+        .unwrap()
+        .project(&["x", "intensity"]);
+    assert!(result.is_err());
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use anyhow::Result;
+use itertools::Itertools as _;
+use pcd_rs::{DataKind, DynReader, Reader, Schema, ValueKind};
+
+#[test]
+fn write_tuples_and_arrays() -> Result<()> {
+    let points = (0..10)
+        .map(|idx| (idx as f32, idx as f32 * 0.5, -(idx as f32), idx as u8))
+        .collect_vec();
+
+    for data_kind in [DataKind::Ascii, DataKind::Binary] {
+        let bytes = common::write_points(&points, data_kind, None)?;
+        let reader = DynReader::from_bytes(&bytes)?;
+        let fields = reader
+            .meta()
+            .field_defs
+            .iter()
+            .map(|def| (def.name.as_str(), def.kind))
+            .collect_vec();
+        assert_eq!(
+            fields,
+            [
+                ("x", ValueKind::F32),
+                ("y", ValueKind::F32),
+                ("z", ValueKind::F32),
+                ("field_3", ValueKind::U8)
+            ]
+        );
+
+        let load_points: Vec<(f32, f32, f32, u8)> = common::read_points(&bytes)?;
+        assert_eq!(load_points, points);
+    }
+
+    // Field names are given by the schema
+    let points = (0..10).map(|idx| [idx as f64; 4]).collect_vec();
+    let schema: Schema = ["x", "y", "z", "intensity"]
+        .into_iter()
+        .map(|name| (name, ValueKind::F64, 1))
+        .collect();
+    let bytes = common::write_points(&points, DataKind::Binary, Some(schema.clone()))?;
+
+    let reader = Reader::<[f64; 4], _>::from_bytes(&bytes)?;
+    assert_eq!(reader.meta().field_defs, schema);
+    let load_points: Vec<[f64; 4]> = reader.try_collect()?;
+    assert_eq!(load_points, points);
+
+    Ok(())
+}
+
+#[test]
+fn read_tuples_and_arrays() -> Result<()> {
+    let tuples: Vec<(f32, f32, f32, f32)> = Reader::open("test_files/ascii.pcd")?.try_collect()?;
+    let arrays: Vec<[f32; 4]> = Reader::open("test_files/ascii.pcd")?.try_collect()?;
+    assert_eq!(tuples.len(), 213);
+    assert!(tuples
+        .iter()
+        .zip(&arrays)
+        .all(|(&(x, y, z, rgb), array)| [x, y, z, rgb] == *array));
+
+    let points: Vec<(f32, f32, f32, u32)> = Reader::open("test_files/binary.pcd")?.try_collect()?;
+    assert_eq!(points.len(), 28944);
+
+    // Kinds and the number of fields are checked
+    assert!(Reader::<(f32, f32, f32, u32), _>::open("test_files/ascii.pcd").is_err());
+    assert!(Reader::<[f32; 3], _>::open("test_files/ascii.pcd").is_err());
+    Ok(())
+}
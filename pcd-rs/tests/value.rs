@@ -0,0 +1,92 @@
+#![cfg(feature = "derive")]
+
+mod common;
+
+use anyhow::Result;
+use itertools::Itertools as _;
+use pcd_rs::{DataKind, Error, PcdDeserialize, PcdSerialize, PcdValue, Reader};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PcdValue)]
+#[repr(u16)]
+pub enum Class {
+    Ground = 2,
+    Vegetation = 5,
+    Building = 300,
+}
+
+#[derive(Debug, Clone, PartialEq, PcdDeserialize, PcdSerialize)]
+pub struct Point {
+    x: f32,
+    class: Class,
+    valid: bool,
+    flags: [bool; 2],
+}
+
+#[derive(Debug, PcdSerialize)]
+pub struct RawPoint {
+    x: f32,
+    class: u16,
+    valid: u8,
+    flags: [u8; 2],
+}
+
+#[test]
+fn write_enum_and_bool_fields() -> Result<()> {
+    let points = vec![
+        Point {
+            x: 1.0,
+            class: Class::Ground,
+            valid: true,
+            flags: [false, true],
+        },
+        Point {
+            x: 2.0,
+            class: Class::Building,
+            valid: false,
+            flags: [true, true],
+        },
+    ];
+
+    for data_kind in [DataKind::Ascii, DataKind::Binary] {
+        let bytes = common::write_points(&points, data_kind, None)?;
+        let reader = Reader::<Point, _>::from_bytes(&bytes)?;
+        let types = reader
+            .meta()
+            .field_defs
+            .iter()
+            .map(|def| def.kind)
+            .collect_vec();
+        assert_eq!(
+            types,
+            <RawPoint as PcdSerialize>::write_spec()
+                .iter()
+                .map(|def| def.kind)
+                .collect_vec()
+        );
+        let load_points: Vec<Point> = common::read_points(&bytes)?;
+        assert_eq!(load_points, points);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn read_unknown_discriminant() -> Result<()> {
+    let point = RawPoint {
+        x: 0.0,
+        class: 3,
+        valid: 1,
+        flags: [0, 0],
+    };
+    let bytes = common::write_points(&[point], DataKind::Binary, None)?;
+    let error = Reader::<Point, _>::from_bytes(&bytes)?
+        .next()
+        .unwrap()
+        .unwrap_err();
+
+    assert!(matches!(
+        error.downcast_ref::<Error>(),
+        Some(Error::UnknownDiscriminantError { value: 3, .. })
+    ));
+    Ok(())
+}
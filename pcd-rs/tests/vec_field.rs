@@ -0,0 +1,80 @@
+#![cfg(feature = "derive")]
+
+mod common;
+
+use anyhow::Result;
+use itertools::Itertools as _;
+use pcd_rs::{DataKind, PcdDeserialize, PcdSerialize, Reader, Schema, ValueKind};
+
+#[derive(Debug, Clone, PartialEq, PcdDeserialize, PcdSerialize)]
+pub struct Descriptor {
+    x: f32,
+    histogram: Vec<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq, PcdDeserialize, PcdSerialize)]
+pub struct Labels {
+    #[pcd(count = 3)]
+    labels: Vec<u16>,
+    #[pcd(count = 1, as = "u8")]
+    flags: std::vec::Vec<u32>,
+}
+
+#[test]
+fn write_vec_fields_with_schema() -> Result<()> {
+    let schema: Schema = [("x", ValueKind::F32, 1), ("histogram", ValueKind::F32, 33)]
+        .into_iter()
+        .collect();
+    let points = (0..10)
+        .map(|idx| Descriptor {
+            x: idx as f32,
+            histogram: (0..33).map(|bin| (idx * bin) as f32 / 7.0).collect(),
+        })
+        .collect_vec();
+
+    for data_kind in [DataKind::Ascii, DataKind::Binary] {
+        let bytes = common::write_points(&points, data_kind, Some(schema.clone()))?;
+        let load_points: Vec<Descriptor> = common::read_points(&bytes)?;
+        assert_eq!(load_points, points);
+
+        // The length is checked against the schema
+        let short_point = Descriptor {
+            x: 0.0,
+            histogram: vec![0.0; 125],
+        };
+        assert!(common::write_points(&[short_point], data_kind, Some(schema.clone())).is_err());
+    }
+
+    // The length is unknown without the schema
+    assert!(common::write_points(&points, DataKind::Binary, None).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn write_vec_fields_with_count() -> Result<()> {
+    let points = vec![
+        Labels {
+            labels: vec![1, 2, 3],
+            flags: vec![4],
+        },
+        Labels {
+            labels: vec![7, 8, 9],
+            flags: vec![255],
+        },
+    ];
+    let bytes = common::write_points(&points, DataKind::Binary, None)?;
+
+    let reader = Reader::<Labels, _>::from_bytes(&bytes)?;
+    let fields = reader
+        .meta()
+        .field_defs
+        .iter()
+        .map(|def| (def.kind, def.count))
+        .collect_vec();
+    assert_eq!(fields, vec![(ValueKind::U16, 3), (ValueKind::U8, 1)]);
+    let load_points: Vec<Labels> = reader.try_collect()?;
+    assert_eq!(load_points, points);
+
+    Ok(())
+}
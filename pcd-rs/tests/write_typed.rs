@@ -1,8 +1,11 @@
 #![cfg(feature = "derive")]
+#![allow(clippy::approx_constant)]
 
 use anyhow::Result;
 use itertools::Itertools as _;
-use pcd_rs::{DataKind, Field, PcdDeserialize, PcdSerialize, Reader, WriterInit};
+use pcd_rs::{
+    AsciiFormat, DataKind, FloatFormat, PcdDeserialize, PcdSerialize, Reader, WriterInit,
+};
 
 #[derive(Debug, PcdDeserialize, PcdSerialize, PartialEq)]
 pub struct Point {
@@ -34,11 +37,12 @@ fn write_ascii_typed() -> Result<()> {
     ];
 
     let mut writer = WriterInit {
-        width: 300,
+        width: dump_points.len() as u64,
         height: 1,
         viewpoint: Default::default(),
         data_kind: DataKind::Ascii,
         schema: None,
+        ..Default::default()
     }
     .create(path)?;
 
@@ -80,11 +84,12 @@ fn write_binary_typed() -> Result<()> {
     ];
 
     let mut writer = WriterInit {
-        width: 300,
+        width: dump_points.len() as u64,
         height: 1,
         viewpoint: Default::default(),
         data_kind: DataKind::Binary,
         schema: None,
+        ..Default::default()
     }
     .create(path)?;
 
@@ -102,3 +107,150 @@ fn write_binary_typed() -> Result<()> {
 
     Ok(())
 }
+
+#[derive(Debug, PcdDeserialize, PcdSerialize, PartialEq)]
+#[repr(C)]
+#[pcd(pod)]
+pub struct PackedPoint {
+    x: f32,
+    y: f32,
+    z: f32,
+    rgba: [u8; 4],
+}
+
+// The same layout without #[pcd(pod)] is written field by field
+#[derive(Debug, PcdDeserialize, PcdSerialize, PartialEq)]
+#[repr(C)]
+pub struct UnmarkedPoint {
+    x: f32,
+    y: f32,
+    z: f32,
+    rgba: [u8; 4],
+}
+
+#[derive(Debug, PcdDeserialize, PcdSerialize, PartialEq)]
+#[repr(C)]
+pub struct PaddedPoint {
+    x: f64,
+    label: u8,
+}
+
+/// A writer that counts write calls, which tells a bulk write from
+/// writes of each field.
+#[derive(Default)]
+struct CountingWriter {
+    inner: std::io::Cursor<Vec<u8>>,
+    writes: usize,
+}
+
+impl std::io::Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writes += 1;
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl std::io::Seek for CountingWriter {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+fn count_bulk_writes<T: PcdSerialize>(points: &[T]) -> Result<usize> {
+    let mut output = CountingWriter::default();
+    let mut writer = WriterInit::default().build_from_writer(&mut output)?;
+    writer.push_all(points)?;
+    writer.finish()?;
+    Ok(output.writes)
+}
+
+fn write_both_ways<T>(points: &[T]) -> Result<()>
+where
+    T: PcdSerialize + PcdDeserialize + PartialEq + std::fmt::Debug,
+{
+    let write = |bulk: bool| -> Result<Vec<u8>> {
+        let mut output = std::io::Cursor::new(vec![]);
+        let mut writer = WriterInit::default().build_from_writer(&mut output)?;
+        if bulk {
+            writer.push_all(points)?;
+        } else {
+            writer.extend(points)?;
+        }
+        writer.finish()?;
+        Ok(output.into_inner())
+    };
+
+    let bulk_bytes = write(true)?;
+    assert_eq!(bulk_bytes, write(false)?);
+
+    let load_points: Vec<T> = Reader::from_bytes(&bulk_bytes)?.try_collect()?;
+    assert_eq!(load_points, points);
+
+    Ok(())
+}
+
+#[test]
+fn push_all_binary_typed() -> Result<()> {
+    let packed: Vec<_> = (0..100)
+        .map(|index| PackedPoint {
+            x: index as f32,
+            y: -(index as f32),
+            z: 0.5,
+            rgba: [index as u8, 1, 2, 3],
+        })
+        .collect();
+    write_both_ways(&packed)?;
+
+    // Only pod records are written in one block
+    let unmarked: Vec<_> = packed
+        .iter()
+        .map(|point| UnmarkedPoint {
+            x: point.x,
+            y: point.y,
+            z: point.z,
+            rgba: point.rgba,
+        })
+        .collect();
+    assert!(count_bulk_writes(&packed)? < packed.len());
+    assert!(count_bulk_writes(&unmarked)? > packed.len());
+
+    let padded: Vec<_> = (0..100)
+        .map(|index| PaddedPoint {
+            x: index as f64 * 0.25,
+            label: index as u8,
+        })
+        .collect();
+    write_both_ways(&padded)?;
+
+    Ok(())
+}
+
+#[test]
+fn write_ascii_typed_formatted() -> Result<()> {
+    let mut output = std::io::Cursor::new(vec![]);
+    let mut writer = WriterInit {
+        data_kind: DataKind::Ascii,
+        ascii_format: AsciiFormat {
+            fields: vec![("new_x".to_string(), FloatFormat::Fixed(2))],
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+    .build_from_writer(&mut output)?;
+
+    writer.push(&Point {
+        x: 3.14159,
+        y: [2, 1, 7],
+        z: -5,
+    })?;
+    writer.finish()?;
+
+    let text = String::from_utf8(output.into_inner())?;
+    assert!(text.ends_with("DATA ascii\n3.14 2 1 7 -5\n"));
+
+    Ok(())
+}
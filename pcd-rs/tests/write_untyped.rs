@@ -1,6 +1,12 @@
+#![allow(clippy::approx_constant)]
+
 use anyhow::Result;
 use itertools::Itertools as _;
-use pcd_rs::{DataKind, DynRecord, Field, Reader, Schema, ValueKind, WriterInit};
+use pcd_rs::{
+    AsciiFormat, DataKind, DynRecord, Field, FloatFormat, Reader, Schema, ValueKind, ViewPoint,
+    WriterInit,
+};
+use std::io::Cursor;
 
 #[test]
 fn write_ascii_untyped() -> Result<()> {
@@ -30,11 +36,12 @@ fn write_ascii_untyped() -> Result<()> {
     ]);
 
     let mut writer = WriterInit {
-        width: 300,
+        width: dump_points.len() as u64,
         height: 1,
         viewpoint: Default::default(),
         data_kind: DataKind::Ascii,
         schema: Some(schema),
+        ..Default::default()
     }
     .create(path)?;
 
@@ -82,11 +89,12 @@ fn write_binary_untyped() -> Result<()> {
     ]);
 
     let mut writer = WriterInit {
-        width: 300,
+        width: dump_points.len() as u64,
         height: 1,
         viewpoint: Default::default(),
         data_kind: DataKind::Binary,
         schema: Some(schema),
+        ..Default::default()
     }
     .create(path)?;
 
@@ -104,3 +112,42 @@ fn write_binary_untyped() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn write_ascii_formatted() -> Result<()> {
+    let schema = Schema::from_iter([
+        ("x", ValueKind::F32, 2),
+        ("t", ValueKind::F64, 1),
+        ("label", ValueKind::U8, 1),
+    ]);
+
+    let mut output = Cursor::new(vec![]);
+    let mut writer = WriterInit {
+        viewpoint: ViewPoint {
+            tx: 0.5,
+            ..Default::default()
+        },
+        data_kind: DataKind::Ascii,
+        schema: Some(schema),
+        ascii_format: AsciiFormat {
+            float: FloatFormat::Fixed(3),
+            fields: vec![("t".to_string(), FloatFormat::Scientific(2))],
+            viewpoint: FloatFormat::Fixed(1),
+        },
+        ..Default::default()
+    }
+    .build_from_writer(&mut output)?;
+
+    writer.push(&DynRecord(vec![
+        Field::F32(vec![3.14159, -2.0]),
+        Field::F64(vec![0.00123]),
+        Field::U8(vec![7]),
+    ]))?;
+    writer.finish()?;
+
+    let text = String::from_utf8(output.into_inner())?;
+    assert!(text.contains("VIEWPOINT 0.5 0.0 0.0 1.0 0.0 0.0 0.0\n"));
+    assert!(text.ends_with("DATA ascii\n3.142 -2.000 1.23e-3 7\n"));
+
+    Ok(())
+}